@@ -0,0 +1,258 @@
+//! Pluggable authentication backends for `/api/query`, selected by
+//! [`crate::config::Config::auth`].
+//!
+//! `ssh_whitelist` (the default) is the original behavior: a caller proves
+//! who they are by presenting the SSH public key already on file for that
+//! username, sourced from [`crate::user_provider`]. `oidc` instead trusts a
+//! configured SSO issuer - a caller proves who they are with a bearer
+//! token, and a configured claim maps to the workspace username - so a
+//! deployment can onboard users through its existing SSO instead of an
+//! admin hand-registering each one's SSH key up front.
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::{
+    client::Client,
+    config::{self, User},
+    AnyError,
+};
+
+/// Credentials presented with a single `/api/query` request, see
+/// [`AuthBackend::verify`].
+pub enum Credentials<'a> {
+    /// Proof of identity is the SSH public key already on file for
+    /// `username`, checked by [`SshWhitelistBackend`].
+    SshKey {
+        username: &'a str,
+        ssh_public_key: &'a str,
+    },
+    /// Proof of identity is a bearer token issued by the configured OIDC
+    /// issuer, checked by [`OidcBackend`]. `ssh_public_key` still travels
+    /// alongside it - OIDC replaces needing an admin to pre-register the
+    /// user, not the workspace pod's own SSH key.
+    Bearer {
+        token: &'a str,
+        ssh_public_key: &'a str,
+    },
+}
+
+/// Resolves [`Credentials`] to a [`User`], or fails if they aren't valid.
+#[async_trait]
+pub trait AuthBackend: Send + Sync {
+    async fn verify(&self, credentials: &Credentials<'_>) -> Result<User, AnyError>;
+
+    /// The full set of usernames this backend currently recognizes, for
+    /// [`crate::operator::Operator::gc_orphaned_workspaces`] to tell a
+    /// legitimate user apart from one that was actually removed. `None`
+    /// means the backend has no bounded list to enumerate (eg.
+    /// [`OidcBackend`] - any bearer token signed by the trusted issuer is
+    /// implicitly legitimate), in which case that GC pass should be skipped
+    /// entirely rather than treating every live workspace as orphaned.
+    async fn known_usernames(&self) -> Result<Option<std::collections::HashSet<String>>, AnyError>;
+}
+
+/// The original behavior: identity is proven by matching a username to the
+/// SSH public key on file, sourced from [`crate::user_provider`].
+pub struct SshWhitelistBackend {
+    provider: Box<dyn crate::user_provider::UserProvider>,
+}
+
+impl SshWhitelistBackend {
+    pub fn new(provider: Box<dyn crate::user_provider::UserProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl AuthBackend for SshWhitelistBackend {
+    async fn verify(&self, credentials: &Credentials<'_>) -> Result<User, AnyError> {
+        match credentials {
+            Credentials::SshKey {
+                username,
+                ssh_public_key,
+            } => self.provider.verify(username, ssh_public_key).await,
+            Credentials::Bearer { .. } => {
+                anyhow::bail!("this deployment is configured for ssh_whitelist auth, not bearer tokens")
+            }
+        }
+    }
+
+    async fn known_usernames(&self) -> Result<Option<std::collections::HashSet<String>>, AnyError> {
+        Ok(Some(self.provider.known_usernames().await?))
+    }
+}
+
+/// Validates a bearer token against a configured OIDC issuer's JWKS (fetched
+/// via OIDC discovery and cached for the life of the process) and maps
+/// [`OidcBackend::username_claim`] to the workspace username.
+pub struct OidcBackend {
+    issuer: String,
+    audience: String,
+    username_claim: String,
+    http: reqwest::Client,
+    jwks: tokio::sync::RwLock<Option<jsonwebtoken::jwk::JwkSet>>,
+}
+
+impl OidcBackend {
+    pub fn new(issuer: String, audience: String, username_claim: String) -> Self {
+        Self {
+            issuer,
+            audience,
+            username_claim,
+            http: reqwest::Client::new(),
+            jwks: tokio::sync::RwLock::new(None),
+        }
+    }
+
+    /// Fetch and cache the issuer's JWKS via the standard OIDC discovery
+    /// document. Cached indefinitely - a deployment rotating its signing
+    /// keys is expected to restart the operator, like any other config
+    /// change.
+    async fn jwks(&self) -> Result<jsonwebtoken::jwk::JwkSet, AnyError> {
+        if let Some(jwks) = self.jwks.read().await.clone() {
+            return Ok(jwks);
+        }
+
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            self.issuer.trim_end_matches('/')
+        );
+        let discovery: serde_json::Value = self
+            .http
+            .get(&discovery_url)
+            .send()
+            .await
+            .context("Could not reach OIDC discovery endpoint")?
+            .json()
+            .await
+            .context("OIDC discovery endpoint did not return valid JSON")?;
+        let jwks_uri = discovery["jwks_uri"]
+            .as_str()
+            .context("OIDC discovery document has no jwks_uri")?;
+
+        let jwks: jsonwebtoken::jwk::JwkSet = self
+            .http
+            .get(jwks_uri)
+            .send()
+            .await
+            .context("Could not fetch OIDC issuer JWKS")?
+            .json()
+            .await
+            .context("OIDC issuer JWKS was not valid")?;
+
+        *self.jwks.write().await = Some(jwks.clone());
+        Ok(jwks)
+    }
+}
+
+#[async_trait]
+impl AuthBackend for OidcBackend {
+    async fn verify(&self, credentials: &Credentials<'_>) -> Result<User, AnyError> {
+        let (token, ssh_public_key) = match credentials {
+            Credentials::Bearer {
+                token,
+                ssh_public_key,
+            } => (*token, *ssh_public_key),
+            Credentials::SshKey { .. } => {
+                anyhow::bail!("this deployment is configured for oidc auth, not a static ssh key whitelist")
+            }
+        };
+
+        let header = jsonwebtoken::decode_header(token).context("Invalid bearer token header")?;
+        let kid = header.kid.clone().context("Bearer token is missing a key ID")?;
+
+        let jwks = self.jwks().await?;
+        let jwk = jwks
+            .find(&kid)
+            .context("Bearer token key ID not found in issuer JWKS")?;
+        let decoding_key =
+            jsonwebtoken::DecodingKey::from_jwk(jwk).context("Unsupported JWKS key type")?;
+
+        // Pin the accepted algorithm to what the issuer's own JWKS entry
+        // declares, rather than trusting the caller-supplied `header.alg` -
+        // an attacker controls that header, so deriving `Validation` from it
+        // directly would let them pick a weaker/mismatched algorithm (eg.
+        // asking us to verify an RSA-signed key's bytes as an HMAC secret)
+        // and forge a signature we'd still accept.
+        let expected_algorithm = jwk_algorithm(jwk)?;
+        anyhow::ensure!(
+            header.alg == expected_algorithm,
+            "Bearer token alg ({:?}) does not match the issuer JWKS key's algorithm ({:?})",
+            header.alg,
+            expected_algorithm
+        );
+
+        let mut validation = jsonwebtoken::Validation::new(expected_algorithm);
+        validation.set_audience(&[&self.audience]);
+        validation.set_issuer(&[&self.issuer]);
+
+        let claims = jsonwebtoken::decode::<serde_json::Value>(token, &decoding_key, &validation)
+            .context("Bearer token failed verification")?
+            .claims;
+
+        let username = claims
+            .get(&self.username_claim)
+            .and_then(|v| v.as_str())
+            .with_context(|| format!("Bearer token is missing the '{}' claim", self.username_claim))?
+            .to_string();
+
+        Ok(User {
+            username,
+            ssh_public_key: ssh_public_key.to_string(),
+            home_volume_size: None,
+        })
+    }
+
+    async fn known_usernames(&self) -> Result<Option<std::collections::HashSet<String>>, AnyError> {
+        // Any caller holding a valid token from `self.issuer` is implicitly
+        // legitimate - there's no local whitelist to enumerate.
+        Ok(None)
+    }
+}
+
+/// The JWKS-declared signature algorithm for `jwk`, used to pin
+/// [`jsonwebtoken::Validation`] instead of trusting the bearer token's own
+/// header - see the comment in [`OidcBackend::verify`].
+fn jwk_algorithm(jwk: &jsonwebtoken::jwk::Jwk) -> Result<jsonwebtoken::Algorithm, AnyError> {
+    let key_algorithm = jwk
+        .common
+        .key_algorithm
+        .context("JWKS key is missing its `alg`, can't pin an expected signature algorithm")?;
+
+    use jsonwebtoken::{jwk::KeyAlgorithm, Algorithm};
+    Ok(match key_algorithm {
+        KeyAlgorithm::HS256 => Algorithm::HS256,
+        KeyAlgorithm::HS384 => Algorithm::HS384,
+        KeyAlgorithm::HS512 => Algorithm::HS512,
+        KeyAlgorithm::RS256 => Algorithm::RS256,
+        KeyAlgorithm::RS384 => Algorithm::RS384,
+        KeyAlgorithm::RS512 => Algorithm::RS512,
+        KeyAlgorithm::PS256 => Algorithm::PS256,
+        KeyAlgorithm::PS384 => Algorithm::PS384,
+        KeyAlgorithm::PS512 => Algorithm::PS512,
+        KeyAlgorithm::ES256 => Algorithm::ES256,
+        KeyAlgorithm::ES384 => Algorithm::ES384,
+        KeyAlgorithm::EdDSA => Algorithm::EdDSA,
+        other => anyhow::bail!("Unsupported JWKS key algorithm: {other:?}"),
+    })
+}
+
+/// Build the configured [`AuthBackend`] for the operator.
+pub async fn build(config: &config::Config, client: Client) -> Result<Box<dyn AuthBackend>, AnyError> {
+    match &config.auth {
+        config::AuthConfig::SshWhitelist => {
+            let provider = crate::user_provider::build(config, client).await?;
+            Ok(Box::new(SshWhitelistBackend::new(provider)))
+        }
+        config::AuthConfig::Oidc {
+            issuer,
+            audience,
+            username_claim,
+        } => Ok(Box::new(OidcBackend::new(
+            issuer.clone(),
+            audience.clone(),
+            username_claim.clone(),
+        ))),
+    }
+}
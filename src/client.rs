@@ -2,13 +2,26 @@
 
 use anyhow::{anyhow, Context};
 use k8s_openapi::{
-    api::core::v1::{Namespace, Node, PersistentVolumeClaim, Pod, Service},
+    api::{
+        apps::v1::StatefulSet,
+        batch::v1::CronJob,
+        core::v1::{
+            Namespace, Node, PersistentVolumeClaim, PersistentVolumeClaimSpec, Pod,
+            ResourceRequirements, Service,
+        },
+        storage::v1::StorageClass,
+    },
     apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
-    apimachinery::pkg::api::resource::Quantity,
+    apimachinery::pkg::{api::resource::Quantity, apis::meta::v1::Status},
     NamespaceResourceScope,
 };
+use kube_runtime::watcher::{self, Event};
 use kube::{
-    api::{AttachParams, DeleteParams, ListParams, ObjectList, ObjectMeta, Patch, PatchParams},
+    api::{
+        AttachedProcess, AttachParams, DeleteParams, ListParams, LogParams, ObjectList,
+        ObjectMeta, Patch, PatchParams,
+    },
+    core::{ApiResource, DynamicObject, GroupVersionKind},
     Api,
 };
 
@@ -47,6 +60,24 @@ impl Client {
         Api::<Node>::all(self.kube.clone()).get(name).await
     }
 
+    /// Fetch the kubelet's `/stats/summary` report for `node_name`, proxied
+    /// through the API server. Used for byte-rate-based network idle
+    /// detection, see
+    /// [`crate::operator::Operator::analyze_pod_autoshutdown`].
+    pub async fn node_stats_summary(&self, node_name: &str) -> Result<NodeStatsSummary, AnyError> {
+        let request = http::Request::builder()
+            .uri(format!(
+                "/api/v1/nodes/{}/proxy/stats/summary",
+                node_name
+            ))
+            .body(Vec::new())
+            .context("Could not build stats/summary request")?;
+        self.kube
+            .request(request)
+            .await
+            .context("Could not fetch kubelet stats/summary")
+    }
+
     /// Get a namespace.
     /// Fails if not found.
     pub async fn namespace(&self, name: &str) -> Result<Namespace, kube::Error> {
@@ -65,6 +96,12 @@ impl Client {
             .await
     }
 
+    /// Optionally get a `StorageClass`, eg. to check whether it allows
+    /// volume expansion before patching a `PersistentVolumeClaim`'s size.
+    pub async fn storage_class_opt(&self, name: &str) -> Result<Option<StorageClass>, kube::Error> {
+        Self::api_result_opt(Api::<StorageClass>::all(self.kube.clone()).get(name).await)
+    }
+
     /// Get a `PersistentVolumeClaim`.
     /// Fails if not found.
     pub async fn volume_claim(
@@ -97,15 +134,153 @@ impl Client {
             .await
     }
 
-    // pub async fn pod_metrics(
-    //     &self,
-    //     namespace: &str,
-    //     pod_name: &str,
-    // ) -> Result<PodMetrics, kube::Error> {
-    //     Api::<PodMetrics>::namespaced(self.kube.clone(), namespace)
-    //         .get(pod_name)
-    //         .await
-    // }
+    /// Get all `PersistentVolumeClaim`s from a namespace, optionally
+    /// filtered by a `key=value` label selector.
+    pub async fn volume_claims_all(
+        &self,
+        namespace: &str,
+        label_selector: Option<(String, String)>,
+    ) -> Result<Vec<PersistentVolumeClaim>, kube::Error> {
+        let sel = label_selector.map(|(key, value)| format!("{}={}", key, value));
+        let mut claims = Vec::new();
+        let api = Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), namespace);
+        let mut params = kube::api::ListParams {
+            label_selector: sel.clone(),
+            limit: Some(500),
+            continue_token: None,
+            ..Default::default()
+        };
+
+        loop {
+            let list = api.list(&params).await?;
+            claims.extend(list.items);
+            if list.metadata.continue_.is_none() {
+                break;
+            }
+            params.continue_token = list.metadata.continue_;
+        }
+        Ok(claims)
+    }
+
+    /// Patch a `PersistentVolumeClaim`, eg. to grow its storage request to
+    /// trigger a CSI volume expansion.
+    pub async fn volume_claim_patch(
+        &self,
+        namespace: &str,
+        name: &str,
+        patch: &Patch<PersistentVolumeClaim>,
+    ) -> Result<PersistentVolumeClaim, kube::Error> {
+        Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), namespace)
+            .patch(name, &PatchParams::default(), patch)
+            .await
+    }
+
+    /// Delete a `PersistentVolumeClaim`.
+    pub async fn volume_claim_delete(&self, namespace: &str, name: &str) -> Result<(), kube::Error> {
+        Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), namespace)
+            .delete(
+                name,
+                &DeleteParams {
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Idempotently provision a `PersistentVolumeClaim`, returning the
+    /// existing claim if one with this name already exists.
+    pub async fn provision_volume_claim(
+        &self,
+        namespace: &str,
+        name: &str,
+        spec: &VolumeClaimSpec,
+    ) -> Result<PersistentVolumeClaim, AnyError> {
+        if let Some(claim) = self.volume_claim_opt(namespace, name).await? {
+            return Ok(claim);
+        }
+
+        parse_quantity_rational(&Quantity(spec.storage.clone()))
+            .context("Invalid volume claim storage size")?;
+
+        let claim = PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some(namespace.to_string()),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                storage_class_name: spec.storage_class_name.clone(),
+                access_modes: Some(spec.access_modes.clone()),
+                resources: Some(ResourceRequirements {
+                    requests: Some(
+                        vec![("storage".to_string(), Quantity(spec.storage.clone()))]
+                            .into_iter()
+                            .collect(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.volume_claim_create(namespace, &claim)
+            .await
+            .context("Could not create persistent volume claim")
+    }
+
+    /// Poll a `PersistentVolumeClaim` until its status phase is `Bound`, or
+    /// `timeout` elapses.
+    ///
+    /// Workspace pods that mount a persistent home directory need the claim
+    /// actually bound before the pod is scheduled onto a node.
+    pub async fn wait_volume_claim_bound(
+        &self,
+        namespace: &str,
+        name: &str,
+        timeout: std::time::Duration,
+    ) -> Result<PersistentVolumeClaim, AnyError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(claim) = self.volume_claim_opt(namespace, name).await? {
+                let phase = claim.status.as_ref().and_then(|s| s.phase.as_deref());
+                if phase == Some("Bound") {
+                    return Ok(claim);
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Timed out waiting for volume claim '{}' to become Bound",
+                    name
+                ));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+    }
+
+    /// Get the metrics of a single pod.
+    /// Fails if not found (eg. if metrics-server is not installed or has not
+    /// yet scraped the pod).
+    pub async fn pod_metrics(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+    ) -> Result<PodMetrics, kube::Error> {
+        Api::<PodMetrics>::namespaced(self.kube.clone(), namespace)
+            .get(pod_name)
+            .await
+    }
+
+    /// Optionally get the metrics of a single pod.
+    pub async fn pod_metrics_opt(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+    ) -> Result<Option<PodMetrics>, kube::Error> {
+        Self::api_result_opt(self.pod_metrics(namespace, pod_name).await)
+    }
 
     /// Paginated pod metrics.
     pub async fn pod_metrics_list(
@@ -161,22 +336,96 @@ impl Client {
             .await
     }
 
-    // pub async fn custom_resource_dynamic_get_by_name_opt(
-    //     &self,
-    //     api_group: &str,
-    //     api_version: &str,
-    //     kind: &str,
-    //     namespace: &str,
-    //     resource_name: &str,
-    // ) -> Result<Option<DynamicObject>, kube::Error> {
+    /// Build a dynamic `Api` handle for an arbitrary GVK, discovered at
+    /// runtime rather than known at compile time.
+    fn dynamic_api(&self, group: &str, version: &str, kind: &str, namespace: Option<&str>) -> Api<DynamicObject> {
+        let gvk = GroupVersionKind::gvk(group, version, kind);
+        let resource = ApiResource::from_gvk(&gvk);
+        match namespace {
+            Some(ns) => Api::namespaced_with(self.kube.clone(), ns, &resource),
+            None => Api::all_with(self.kube.clone(), &resource),
+        }
+    }
 
-    //     let gvk = GroupVersionKind::gvk(api_group, api_version, kind);
-    //     let air = ApiResource::from_gvk(&gvk);
-    //     let api = Api::<DynamicObject>::namespaced_with(self.kube.clone(), namespace, &air);
+    /// Get a custom resource by GVK and name, without a compile-time type.
+    /// Use [`Client::custom_resource_definition_by_name`] to check that the
+    /// CRD is actually installed first.
+    pub async fn dynamic_get_opt(
+        &self,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> Result<Option<DynamicObject>, kube::Error> {
+        let res = self.dynamic_api(group, version, kind, namespace).get(name).await;
+        Self::api_result_opt(res)
+    }
 
-    //     let res = api.get(resource_name).await;
-    //     Self::api_result_opt(res)
-    // }
+    /// List custom resources by GVK, paginated like [`Client::pod_metrics_list`].
+    pub async fn dynamic_list(
+        &self,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+        label_selector: Option<String>,
+        cursor: Option<String>,
+    ) -> Result<ObjectList<DynamicObject>, kube::Error> {
+        self.dynamic_api(group, version, kind, namespace)
+            .list(&ListParams {
+                label_selector,
+                limit: Some(500),
+                continue_token: cursor,
+                ..Default::default()
+            })
+            .await
+    }
+
+    /// Create a custom resource by GVK.
+    pub async fn dynamic_create(
+        &self,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+        obj: &DynamicObject,
+    ) -> Result<DynamicObject, kube::Error> {
+        self.dynamic_api(group, version, kind, namespace)
+            .create(&Default::default(), obj)
+            .await
+    }
+
+    /// Patch a custom resource by GVK.
+    pub async fn dynamic_patch(
+        &self,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+        name: &str,
+        patch: &Patch<DynamicObject>,
+        params: &PatchParams,
+    ) -> Result<DynamicObject, kube::Error> {
+        self.dynamic_api(group, version, kind, namespace)
+            .patch(name, params, patch)
+            .await
+    }
+
+    /// Delete a custom resource by GVK.
+    pub async fn dynamic_delete(
+        &self,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> Result<(), kube::Error> {
+        self.dynamic_api(group, version, kind, namespace)
+            .delete(name, &DeleteParams::default())
+            .await?;
+        Ok(())
+    }
 
     pub async fn custom_resource_definition_by_name(
         &self,
@@ -188,6 +437,100 @@ impl Client {
         Self::api_result_opt(res)
     }
 
+    /// Server-side apply: idempotently reconcile `obj` against the cluster
+    /// under `field_manager`, letting Kubernetes merge managed fields instead
+    /// of failing with `409 Conflict` like the `*_create` methods do when
+    /// the object already exists.
+    ///
+    /// `namespace` should be `None` for cluster-scoped resources (eg.
+    /// `Namespace`) and `Some` for namespaced ones (`Pod`, `Service`,
+    /// `PersistentVolumeClaim`, `ServiceMonitor`, ...).
+    pub async fn apply<K>(
+        &self,
+        namespace: Option<&str>,
+        obj: &K,
+        field_manager: &str,
+    ) -> Result<K, kube::Error>
+    where
+        K: kube::Resource<DynamicType = ()> + Clone + std::fmt::Debug,
+        K: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let name = obj
+            .meta()
+            .name
+            .clone()
+            .expect("object passed to Client::apply must have a name");
+        let api: Api<K> = match namespace {
+            Some(ns) => Api::namespaced(self.kube.clone(), ns),
+            None => Api::all(self.kube.clone()),
+        };
+        api.patch(
+            &name,
+            &PatchParams::apply(field_manager).force(),
+            &Patch::Apply(obj),
+        )
+        .await
+    }
+
+    /// Get an existing resource by name, or create it with `make` if it
+    /// doesn't exist yet - like the hand-written `ensure_*`/`*_opt` +
+    /// `*_create` pairs elsewhere in this file, but for any resource type,
+    /// avoiding the `409 Conflict` dance for the simple case.
+    pub async fn get_or_create<K>(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        make: impl FnOnce() -> K,
+    ) -> Result<K, kube::Error>
+    where
+        K: kube::Resource<DynamicType = ()> + Clone + std::fmt::Debug,
+        K: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let api: Api<K> = match namespace {
+            Some(ns) => Api::namespaced(self.kube.clone(), ns),
+            None => Api::all(self.kube.clone()),
+        };
+        if let Some(existing) = Self::api_result_opt(api.get(name).await)? {
+            return Ok(existing);
+        }
+        api.create(&Default::default(), &make()).await
+    }
+
+    /// Build a typed [`Api`] handle for any resource kind, namespaced or
+    /// cluster-scoped. Used by callers (eg. [`kube_runtime::Controller`])
+    /// that need raw `Api`/watcher access beyond the CRUD helpers above.
+    pub fn api<K>(&self, namespace: Option<&str>) -> Api<K>
+    where
+        K: kube::Resource<DynamicType = ()>,
+    {
+        match namespace {
+            Some(ns) => Api::namespaced(self.kube.clone(), ns),
+            None => Api::all(self.kube.clone()),
+        }
+    }
+
+    /// Watch any resource kind, namespaced or cluster-scoped, like
+    /// [`Client::watch_pods`]/[`Client::watch_services`] but for a type not
+    /// known until a caller (eg. [`crate::user_provider::CrdUserProvider`])
+    /// names it.
+    pub fn watch<K>(&self, namespace: Option<&str>) -> impl futures::Stream<Item = Result<Event<K>, watcher::Error>>
+    where
+        K: kube::Resource<DynamicType = ()> + Clone + std::fmt::Debug + Send + Sync + 'static,
+        K: serde::de::DeserializeOwned,
+    {
+        watcher::watcher(self.api(namespace), ListParams::default())
+    }
+
+    /// Idempotently install a compile-time-known `CustomResourceDefinition`
+    /// (eg. `WorkspaceUser::crd()`) via the same server-side apply used for
+    /// the other managed objects - safe to call on every startup.
+    pub async fn register_crd<K>(&self, field_manager: &str) -> Result<CustomResourceDefinition, kube::Error>
+    where
+        K: kube::core::CustomResourceExt,
+    {
+        self.apply(None, &K::crd(), field_manager).await
+    }
+
     // Get paginated pods from a namespace.
     // pub async fn pods(
     //     &self,
@@ -324,6 +667,289 @@ impl Client {
         }
     }
 
+    /// Run a command inside a pod over the Kubernetes API server's exec
+    /// WebSocket, capturing both stdout and stderr plus the exit status.
+    ///
+    /// This is like [`Client::pod_exec_stdout`] but does not require the
+    /// command to succeed and also collects stderr, which makes it suitable
+    /// for relaying the full result of a command back to a caller instead of
+    /// only using it for internal checks.
+    pub async fn pod_exec(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: &str,
+        command: Vec<&str>,
+        tty: bool,
+    ) -> Result<PodExecOutput, AnyError> {
+        use tokio::io::AsyncReadExt;
+
+        let params = AttachParams {
+            container: Some(container.to_string()),
+            stdin: false,
+            stdout: true,
+            stderr: !tty,
+            tty,
+            ..Default::default()
+        };
+        let mut proc = Api::<Pod>::namespaced(self.kube.clone(), namespace)
+            .exec(pod, command, &params)
+            .await?;
+
+        let mut stdout_stream = proc.stdout();
+        let mut stderr_stream = proc.stderr();
+
+        let read_stdout = async {
+            let mut buf = String::new();
+            if let Some(stream) = stdout_stream.as_mut() {
+                stream
+                    .read_to_string(&mut buf)
+                    .await
+                    .context("Could not read stdout")?;
+            }
+            Result::<_, AnyError>::Ok(buf)
+        };
+        let read_stderr = async {
+            let mut buf = String::new();
+            if let Some(stream) = stderr_stream.as_mut() {
+                stream
+                    .read_to_string(&mut buf)
+                    .await
+                    .context("Could not read stderr")?;
+            }
+            Result::<_, AnyError>::Ok(buf)
+        };
+        let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)?;
+
+        // The channel-3 status frame carries the exit status of the command
+        // as a Kubernetes `Status` object.
+        let status = proc.await;
+        let success = status
+            .as_ref()
+            .and_then(|s| s.status.as_ref())
+            .map(|s| s == "Success")
+            .unwrap_or(false);
+        let exit_code = status.as_ref().and_then(exec_status_exit_code);
+
+        Ok(PodExecOutput {
+            stdout,
+            stderr,
+            success,
+            exit_code,
+        })
+    }
+
+    /// Like [`Client::pod_exec`], but attaches stdin and returns the process'
+    /// stdin writer and stdout/stderr readers directly instead of buffering
+    /// the whole exchange in memory.
+    ///
+    /// This is suitable for tunneling a long-lived, bidirectional protocol
+    /// (eg. a `git` upload-pack/receive-pack session) through a single pod
+    /// exec. Call [`PodExecSession::wait`] once both sides of the stream are
+    /// done to obtain the command's exit code.
+    pub async fn pod_exec_stream(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: &str,
+        command: Vec<&str>,
+    ) -> Result<PodExecSession, AnyError> {
+        let params = AttachParams {
+            container: Some(container.to_string()),
+            stdin: true,
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+        let proc = Api::<Pod>::namespaced(self.kube.clone(), namespace)
+            .exec(pod, command, &params)
+            .await?;
+
+        Ok(PodExecSession { proc })
+    }
+
+    /// Like [`Client::pod_exec_stream`], but attaches a `tty` instead of
+    /// separate stdout/stderr streams, suitable for an interactive shell
+    /// session. The caller pumps bytes between its own socket (eg. a
+    /// websocket proxying a browser or CLI terminal) and the returned
+    /// session's `stdin`/`stdout` halves.
+    pub async fn pod_exec_interactive(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: &str,
+        command: Vec<&str>,
+    ) -> Result<PodExecSession, AnyError> {
+        let params = AttachParams {
+            container: Some(container.to_string()),
+            stdin: true,
+            stdout: true,
+            stderr: false,
+            tty: true,
+            ..Default::default()
+        };
+        let proc = Api::<Pod>::namespaced(self.kube.clone(), namespace)
+            .exec(pod, command, &params)
+            .await?;
+
+        Ok(PodExecSession { proc })
+    }
+
+    /// Run a command inside a pod over the Kubernetes exec WebSocket,
+    /// optionally writing `stdin_data` to the process before reading back
+    /// its raw stdout/stderr.
+    ///
+    /// Unlike [`Client::pod_exec`], stdout/stderr are collected as raw bytes
+    /// rather than `String`, so this is suitable for binary payloads such as
+    /// a `tar` archive piped in or out of a pod (see `Operator::copy_into_user_pod`
+    /// and `Operator::copy_from_user_pod`).
+    pub async fn pod_exec_io(
+        &self,
+        namespace: &str,
+        pod: &str,
+        container: &str,
+        command: Vec<&str>,
+        stdin_data: Option<Vec<u8>>,
+    ) -> Result<PodExecIoOutput, AnyError> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let params = AttachParams {
+            container: Some(container.to_string()),
+            stdin: stdin_data.is_some(),
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+        let mut proc = Api::<Pod>::namespaced(self.kube.clone(), namespace)
+            .exec(pod, command, &params)
+            .await?;
+
+        let write_stdin = async {
+            if let Some(data) = stdin_data {
+                let mut stdin = proc.stdin().ok_or_else(|| anyhow!("Stdin not attached"))?;
+                stdin
+                    .write_all(&data)
+                    .await
+                    .context("Could not write to stdin")?;
+                stdin.shutdown().await.context("Could not close stdin")?;
+            }
+            Result::<_, AnyError>::Ok(())
+        };
+
+        let mut stdout_stream = proc.stdout();
+        let mut stderr_stream = proc.stderr();
+
+        let read_stdout = async {
+            let mut buf = Vec::new();
+            if let Some(stream) = stdout_stream.as_mut() {
+                stream
+                    .read_to_end(&mut buf)
+                    .await
+                    .context("Could not read stdout")?;
+            }
+            Result::<_, AnyError>::Ok(buf)
+        };
+        let read_stderr = async {
+            let mut buf = Vec::new();
+            if let Some(stream) = stderr_stream.as_mut() {
+                stream
+                    .read_to_end(&mut buf)
+                    .await
+                    .context("Could not read stderr")?;
+            }
+            Result::<_, AnyError>::Ok(buf)
+        };
+        let (_, stdout, stderr) = tokio::try_join!(write_stdin, read_stdout, read_stderr)?;
+
+        let status = proc.await;
+        let success = status
+            .as_ref()
+            .and_then(|s| s.status.as_ref())
+            .map(|s| s == "Success")
+            .unwrap_or(false);
+
+        Ok(PodExecIoOutput {
+            stdout,
+            stderr,
+            success,
+        })
+    }
+
+    /// Stream a pod's container logs.
+    /// Set `params.follow` to keep the stream open as new log lines arrive.
+    pub async fn pod_log_stream(
+        &self,
+        namespace: &str,
+        pod: &str,
+        params: &LogParams,
+    ) -> Result<impl tokio::io::AsyncBufRead + Send, kube::Error> {
+        Api::<Pod>::namespaced(self.kube.clone(), namespace)
+            .log_stream(pod, params)
+            .await
+    }
+
+    /// Watch workspace pods in a namespace, filtered by a label.
+    /// Yields `Applied`/`Deleted`/`Restarted` events as the watch progresses,
+    /// resyncing automatically if the underlying watch connection drops.
+    pub fn watch_pods(
+        &self,
+        namespace: &str,
+        label_selector: Option<(String, String)>,
+    ) -> impl futures::Stream<Item = Result<Event<Pod>, watcher::Error>> {
+        let selector = label_selector.map(|(key, value)| format!("{}={}", key, value));
+        let api = Api::<Pod>::namespaced(self.kube.clone(), namespace);
+        watcher::watcher(
+            api,
+            ListParams {
+                label_selector: selector,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Watch all services in a namespace.
+    pub fn watch_services(
+        &self,
+        namespace: &str,
+    ) -> impl futures::Stream<Item = Result<Event<Service>, watcher::Error>> {
+        let api = Api::<Service>::namespaced(self.kube.clone(), namespace);
+        watcher::watcher(api, ListParams::default())
+    }
+
+    /// Watch workspace home-directory `PersistentVolumeClaim`s in a
+    /// namespace, so existence checks (eg. [`Operator::ensure_user_home_volume`])
+    /// can consult a warm watch-updated cache instead of a live `GET` on
+    /// every `/api/query` call.
+    ///
+    /// [`Operator::ensure_user_home_volume`]: crate::operator::Operator::ensure_user_home_volume
+    pub fn watch_volume_claims(
+        &self,
+        namespace: &str,
+    ) -> impl futures::Stream<Item = Result<Event<PersistentVolumeClaim>, watcher::Error>> {
+        let api = Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), namespace);
+        watcher::watcher(api, ListParams::default())
+    }
+
+    /// Watch all nodes in the cluster.
+    pub fn watch_nodes(&self) -> impl futures::Stream<Item = Result<Event<Node>, watcher::Error>> {
+        let api = Api::<Node>::all(self.kube.clone());
+        watcher::watcher(api, ListParams::default())
+    }
+
+    /// Watch all namespaces in the cluster.
+    ///
+    /// Like the other `watch_*` methods, this is backed by
+    /// `kube_runtime::watcher`, which transparently re-lists on a `410 Gone`
+    /// (expired resource version) by emitting a `Restarted` event, so
+    /// callers don't need their own `api_result_opt`-style retry handling
+    /// for that case.
+    pub fn watch_namespaces(
+        &self,
+    ) -> impl futures::Stream<Item = Result<Event<Namespace>, watcher::Error>> {
+        let api = Api::<Namespace>::all(self.kube.clone());
+        watcher::watcher(api, ListParams::default())
+    }
+
     /// Get a `Service`.
     /// Fails if not found.
     pub async fn service(&self, namespace: &str, name: &str) -> Result<Service, kube::Error> {
@@ -364,6 +990,191 @@ impl Client {
             .await?;
         Ok(())
     }
+
+    /// Get a `StatefulSet`.
+    /// Fails if not found.
+    pub async fn statefulset(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<StatefulSet, kube::Error> {
+        Api::<StatefulSet>::namespaced(self.kube.clone(), namespace)
+            .get(name)
+            .await
+    }
+
+    /// Optionally get a `StatefulSet`.
+    pub async fn statefulset_opt(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<StatefulSet>, kube::Error> {
+        Self::api_result_opt(self.statefulset(namespace, name).await)
+    }
+
+    /// Create a new `StatefulSet`.
+    pub async fn statefulset_create(
+        &self,
+        namespace: &str,
+        statefulset: &StatefulSet,
+    ) -> Result<StatefulSet, kube::Error> {
+        Api::<StatefulSet>::namespaced(self.kube.clone(), namespace)
+            .create(&Default::default(), statefulset)
+            .await
+    }
+
+    /// Delete a `StatefulSet`.
+    pub async fn statefulset_delete(&self, namespace: &str, name: &str) -> Result<(), kube::Error> {
+        Api::<StatefulSet>::namespaced(self.kube.clone(), namespace)
+            .delete(
+                name,
+                &DeleteParams {
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Patch a `StatefulSet`, eg. to scale it down to zero replicas for a
+    /// graceful shutdown instead of deleting it outright.
+    pub async fn statefulset_patch(
+        &self,
+        namespace: &str,
+        name: &str,
+        patch: &Patch<StatefulSet>,
+    ) -> Result<StatefulSet, kube::Error> {
+        Api::<StatefulSet>::namespaced(self.kube.clone(), namespace)
+            .patch(name, &PatchParams::default(), patch)
+            .await
+    }
+
+    /// Optionally get a `CronJob`.
+    pub async fn cronjob_opt(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<CronJob>, kube::Error> {
+        let res = Api::<CronJob>::namespaced(self.kube.clone(), namespace)
+            .get(name)
+            .await;
+        Self::api_result_opt(res)
+    }
+
+    /// Create a new `CronJob`.
+    pub async fn cronjob_create(
+        &self,
+        namespace: &str,
+        cronjob: &CronJob,
+    ) -> Result<CronJob, kube::Error> {
+        Api::<CronJob>::namespaced(self.kube.clone(), namespace)
+            .create(&Default::default(), cronjob)
+            .await
+    }
+
+    /// Delete a `CronJob`.
+    pub async fn cronjob_delete(&self, namespace: &str, name: &str) -> Result<(), kube::Error> {
+        Api::<CronJob>::namespaced(self.kube.clone(), namespace)
+            .delete(
+                name,
+                &DeleteParams {
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Desired state for [`Client::provision_volume_claim`].
+#[derive(Clone, Debug)]
+pub struct VolumeClaimSpec {
+    /// Requested storage size, eg. `"10Gi"`.
+    pub storage: String,
+    pub access_modes: Vec<String>,
+    pub storage_class_name: Option<String>,
+}
+
+impl Default for VolumeClaimSpec {
+    fn default() -> Self {
+        Self {
+            storage: "1Gi".to_string(),
+            access_modes: vec!["ReadWriteOnce".to_string()],
+            storage_class_name: None,
+        }
+    }
+}
+
+/// The collected output of a [`Client::pod_exec`] call.
+#[derive(Clone, Debug)]
+pub struct PodExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub success: bool,
+    /// The command's numeric exit code, if it could be recovered from the
+    /// exec channel-3 status frame.
+    pub exit_code: Option<i32>,
+}
+
+/// Streaming handles for a command started with [`Client::pod_exec_stream`].
+/// Holds the underlying `AttachedProcess` alive so its stdin/stdout/stderr
+/// streams stay usable until [`PodExecSession::wait`] is called.
+pub struct PodExecSession {
+    proc: AttachedProcess,
+}
+
+impl PodExecSession {
+    /// Get the process' stdin writer, if stdin was attached and has not
+    /// already been taken.
+    pub fn stdin(&mut self) -> Option<impl tokio::io::AsyncWrite> {
+        self.proc.stdin()
+    }
+
+    /// Get the process' stdout reader, if stdout was attached and has not
+    /// already been taken.
+    pub fn stdout(&mut self) -> Option<impl tokio::io::AsyncRead> {
+        self.proc.stdout()
+    }
+
+    /// Get the process' stderr reader, if stderr was attached and has not
+    /// already been taken.
+    pub fn stderr(&mut self) -> Option<impl tokio::io::AsyncRead> {
+        self.proc.stderr()
+    }
+
+    /// Wait for the command to terminate and return its exit code, if it
+    /// could be recovered from the exec channel-3 status frame.
+    pub async fn wait(self) -> Option<i32> {
+        let status = self.proc.await;
+        status.as_ref().and_then(exec_status_exit_code)
+    }
+}
+
+/// Extract a command's numeric exit code from the exec channel-3 status
+/// frame. Kubernetes reports a plain `"Success"` status on a zero exit, and
+/// embeds the real code in a `NonZeroExitCode`/`ExitCode` status cause
+/// otherwise.
+fn exec_status_exit_code(status: &Status) -> Option<i32> {
+    if status.status.as_deref() == Some("Success") {
+        return Some(0);
+    }
+    status
+        .details
+        .as_ref()?
+        .causes
+        .as_ref()?
+        .iter()
+        .find(|cause| cause.reason.as_deref() == Some("ExitCode"))
+        .and_then(|cause| cause.message.as_ref())
+        .and_then(|msg| msg.parse().ok())
+}
+
+/// The collected output of a [`Client::pod_exec_io`] call.
+#[derive(Clone, Debug)]
+pub struct PodExecIoOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub success: bool,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -408,56 +1219,225 @@ impl k8s_openapi::Metadata for PodMetrics {
     }
 }
 
-/// Parse a Kubernetes API quantity into a i64 representation.
-fn parse_quantity(q: &Quantity) -> Result<i64, AnyError> {
-    let mut number_end_index = 0;
-    let mut chars = q.0.chars().peekable();
+/// A (partial) kubelet `/stats/summary` report, as served by
+/// `GET /api/v1/nodes/{name}/proxy/stats/summary`. Only the per-pod CPU and
+/// network counters used for autoshutdown idle detection are modeled; the
+/// real payload has many more fields.
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+pub struct NodeStatsSummary {
+    #[serde(default)]
+    pub pods: Vec<PodStats>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodStats {
+    #[serde(rename = "podRef")]
+    pub pod_ref: PodStatsReference,
+    pub cpu: Option<PodStatsCpu>,
+    pub network: Option<PodStatsNetwork>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodStatsReference {
+    pub name: String,
+    pub namespace: String,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodStatsCpu {
+    #[serde(rename = "usageNanoCores")]
+    pub usage_nano_cores: Option<u64>,
+}
 
-    match chars.next() {
-        None => {
-            return Err(anyhow!("Empty quantity"));
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodStatsNetwork {
+    #[serde(rename = "rxBytes")]
+    pub rx_bytes: Option<u64>,
+    #[serde(rename = "txBytes")]
+    pub tx_bytes: Option<u64>,
+}
+
+impl NodeStatsSummary {
+    /// Find the stats entry for a specific pod by namespace/name.
+    pub fn pod(&self, namespace: &str, name: &str) -> Option<&PodStats> {
+        self.pods
+            .iter()
+            .find(|p| p.pod_ref.namespace == namespace && p.pod_ref.name == name)
+    }
+}
+
+/// Parse a Kubernetes API quantity into an exact rational `numerator /
+/// denominator` (with `denominator` always a positive power of ten),
+/// preserving full precision for fractional quantities (eg. `1.5Gi`,
+/// `100.5m`) and exponent notation (eg. `1.5e3`) instead of collapsing them
+/// into a lossy `f64`.
+///
+/// Supports the full Kubernetes quantity grammar: an optional sign, a
+/// decimal significand with optional fractional part, an optional base-10
+/// exponent (`e`/`E`), and an optional suffix that is either a decimal SI
+/// suffix (`n`, `u`, `m`, `""`, `k`, `M`, `G`, `T`, `P`, `E`) or a binary
+/// suffix (`Ki`, `Mi`, `Gi`, `Ti`, `Pi`, `Ei`).
+fn parse_quantity_rational(q: &Quantity) -> Result<(i128, i128), AnyError> {
+    let s = q.0.as_str();
+    let bytes = s.as_bytes();
+    if bytes.is_empty() {
+        return Err(anyhow!("Empty quantity"));
+    }
+
+    let mut i = 0usize;
+    let negative = match bytes[i] {
+        b'-' => {
+            i += 1;
+            true
         }
-        Some(x) => {
-            if x.is_ascii_digit() || x == '+' || x == '-' {
-                number_end_index += 1;
-            } else {
-                return Err(anyhow!("Invalid quantity"));
-            }
+        b'+' => {
+            i += 1;
+            false
         }
+        _ => false,
+    };
+
+    let int_start = i;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    let int_part = &s[int_start..i];
+
+    let mut frac_part = "";
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        let frac_start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        frac_part = &s[frac_start..i];
+    }
+
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Err(anyhow!("Invalid quantity"));
+    }
+
+    // An `e`/`E` only starts an exponent when digits follow it; otherwise it
+    // is the start of the `E`/`Ei` suffix.
+    let exponent_follows = {
+        let mut j = i + 1;
+        if j < bytes.len() && (bytes[j] == b'-' || bytes[j] == b'+') {
+            j += 1;
+        }
+        j < bytes.len() && bytes[j].is_ascii_digit()
+    };
+
+    let mut exponent: i32 = 0;
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') && exponent_follows {
+        i += 1;
+        let exp_start = i;
+        if i < bytes.len() && (bytes[i] == b'-' || bytes[i] == b'+') {
+            i += 1;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == exp_start {
+            return Err(anyhow!("Invalid quantity"));
+        }
+        exponent = s[exp_start..i].parse()?;
+    }
+
+    let suffix = &s[i..];
+
+    let mut significand: i128 = format!("{}{}", int_part, frac_part)
+        .parse()
+        .map_err(|_| anyhow!("Invalid quantity"))?;
+    if negative {
+        significand = -significand;
     }
-    while chars.peek().map(|x| x.is_ascii_digit()).unwrap_or(false) {
-        number_end_index += 1;
-        chars.next();
-    }
-
-    let number: i64 = q.0[0..number_end_index].parse()?;
-    let suffix = &q.0[number_end_index..];
-    let mul: f64 = match suffix {
-        "m" => 0.001,
-        "" => 1.0,
-        "k" => 1_000.0,
-        "Ki" => 1_024.0,
-        "M" => 1_000_000.0,
-        "Mi" => 2.0f64.powi(20),
-        "G" => 1_000_000_000.0,
-        "Gi" => 2.0f64.powi(30),
-        "T" => 1_000_000_000_000.0,
-        "Ti" => 2.0f64.powi(40),
-        "P" => 1_000_000_000_000_000.0,
-        "Pi" => 2.0f64.powi(50),
-        "E" => 1_000_000_000_000_000_000.0,
-        "Ei" => 2.0f64.powi(60),
+    let decimal_exponent = exponent - frac_part.len() as i32;
+
+    let (suffix_decimal_exp, suffix_binary_exp): (i32, u32) = match suffix {
+        "n" => (-9, 0),
+        "u" => (-6, 0),
+        "m" => (-3, 0),
+        "" => (0, 0),
+        "k" => (3, 0),
+        "M" => (6, 0),
+        "G" => (9, 0),
+        "T" => (12, 0),
+        "P" => (15, 0),
+        "E" => (18, 0),
+        "Ki" => (0, 10),
+        "Mi" => (0, 20),
+        "Gi" => (0, 30),
+        "Ti" => (0, 40),
+        "Pi" => (0, 50),
+        "Ei" => (0, 60),
         other => return Err(anyhow!("Unknown suffix {}", other)),
     };
 
-    Ok((number as f64 * mul).ceil() as i64)
+    let total_decimal_exp = decimal_exponent + suffix_decimal_exp;
+    significand = significand
+        .checked_mul(1i128 << suffix_binary_exp)
+        .ok_or_else(|| anyhow!("Quantity out of range"))?;
+
+    if total_decimal_exp >= 0 {
+        let scale = 10i128
+            .checked_pow(total_decimal_exp as u32)
+            .ok_or_else(|| anyhow!("Quantity out of range"))?;
+        let numerator = significand
+            .checked_mul(scale)
+            .ok_or_else(|| anyhow!("Quantity out of range"))?;
+        Ok((numerator, 1))
+    } else {
+        let denominator = 10i128
+            .checked_pow((-total_decimal_exp) as u32)
+            .ok_or_else(|| anyhow!("Quantity out of range"))?;
+        Ok((significand, denominator))
+    }
 }
 
-/// Get total pod CPU usage for all containers an a pod.
+/// Add two exact rationals as produced by [`parse_quantity_rational`].
+fn add_rational((n1, d1): (i128, i128), (n2, d2): (i128, i128)) -> (i128, i128) {
+    (n1 * d2 + n2 * d1, d1 * d2)
+}
+
+/// Round `numerator / denominator` (`denominator` must be positive) to the
+/// nearest integer.
+fn round_rational(numerator: i128, denominator: i128) -> i64 {
+    debug_assert!(denominator > 0);
+    let doubled = numerator * 2;
+    let rounded = if numerator >= 0 {
+        (doubled + denominator) / (2 * denominator)
+    } else {
+        (doubled - denominator) / (2 * denominator)
+    };
+    rounded as i64
+}
+
+/// Get total pod CPU usage for all containers in a pod, in millicores
+/// (thousandths of a CPU core). Summed with exact rational arithmetic so
+/// fractional values like `250m + 0.5` add up correctly instead of losing
+/// precision to premature rounding.
 pub fn pod_metrics_total_cpu(metrics: &PodMetrics) -> Result<i64, AnyError> {
-    metrics.containers.iter().try_fold(0i64, |acc, container| {
-        parse_quantity(&container.usage.cpu).map(|x| x + acc)
-    })
+    let total = metrics
+        .containers
+        .iter()
+        .try_fold((0i128, 1i128), |acc, container| {
+            Result::<_, AnyError>::Ok(add_rational(acc, parse_quantity_rational(&container.usage.cpu)?))
+        })?;
+    Ok(round_rational(total.0 * 1000, total.1))
+}
+
+/// Get total pod memory usage (in bytes) for all containers in a pod.
+pub fn pod_metrics_total_memory(metrics: &PodMetrics) -> Result<i64, AnyError> {
+    let total = metrics
+        .containers
+        .iter()
+        .try_fold((0i128, 1i128), |acc, container| {
+            Result::<_, AnyError>::Ok(add_rational(
+                acc,
+                parse_quantity_rational(&container.usage.memory)?,
+            ))
+        })?;
+    Ok(round_rational(total.0, total.1))
 }
 
 pub fn pod_name(pod: &Pod) -> &String {
@@ -480,6 +1460,112 @@ pub fn pod_containers_ready(pod: &Pod) -> bool {
         .unwrap_or_default()
 }
 
+/// A reason a workspace pod's container may be considered unhealthy, as
+/// surfaced by [`pod_suspicious_reasons`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SuspiciousReason {
+    /// The container is waiting to start, eg. `ImagePullBackOff` or
+    /// `CrashLoopBackOff`.
+    ContainerWaiting(Option<String>),
+    /// The container is running but failing its readiness check.
+    NotReady,
+    /// The container has restarted at least once.
+    Restarted {
+        count: i32,
+        exit_code: Option<i32>,
+        reason: Option<String>,
+    },
+    /// The container is currently terminated with a nonzero exit code.
+    TerminatedWithError(i32),
+}
+
+impl std::fmt::Display for SuspiciousReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContainerWaiting(reason) => write!(
+                f,
+                "waiting to start{}",
+                reason
+                    .as_ref()
+                    .map(|r| format!(" ({})", r))
+                    .unwrap_or_default()
+            ),
+            Self::NotReady => write!(f, "running but not passing its readiness check"),
+            Self::Restarted {
+                count,
+                exit_code,
+                reason,
+            } => write!(
+                f,
+                "restarted {} time(s){}{}",
+                count,
+                exit_code
+                    .map(|c| format!(", last exit code {}", c))
+                    .unwrap_or_default(),
+                reason
+                    .as_ref()
+                    .map(|r| format!(" ({})", r))
+                    .unwrap_or_default()
+            ),
+            Self::TerminatedWithError(code) => write!(f, "terminated with exit code {}", code),
+        }
+    }
+}
+
+/// Classify why a pod's containers are unhealthy, one entry per problem
+/// found. Returns an empty `Vec` if all containers look healthy (or the pod
+/// has no status yet).
+pub fn pod_suspicious_reasons(pod: &Pod) -> Vec<(String, SuspiciousReason)> {
+    let mut reasons = Vec::new();
+
+    let statuses = match pod
+        .status
+        .as_ref()
+        .and_then(|s| s.container_statuses.as_ref())
+    {
+        Some(statuses) => statuses,
+        None => return reasons,
+    };
+
+    for status in statuses {
+        let name = &status.name;
+        let state = status.state.as_ref();
+
+        if let Some(waiting) = state.and_then(|s| s.waiting.as_ref()) {
+            reasons.push((
+                name.clone(),
+                SuspiciousReason::ContainerWaiting(waiting.reason.clone()),
+            ));
+        } else if let Some(terminated) = state.and_then(|s| s.terminated.as_ref()) {
+            if terminated.exit_code != 0 {
+                reasons.push((
+                    name.clone(),
+                    SuspiciousReason::TerminatedWithError(terminated.exit_code),
+                ));
+            }
+        } else if state.and_then(|s| s.running.as_ref()).is_some() && !status.ready {
+            reasons.push((name.clone(), SuspiciousReason::NotReady));
+        }
+
+        if status.restart_count > 0 {
+            let last_terminated = status
+                .last_state
+                .as_ref()
+                .and_then(|s| s.terminated.as_ref());
+            reasons.push((
+                name.clone(),
+                SuspiciousReason::Restarted {
+                    count: status.restart_count,
+                    exit_code: last_terminated.map(|t| t.exit_code),
+                    reason: last_terminated.and_then(|t| t.reason.clone()),
+                },
+            ));
+        }
+    }
+
+    reasons
+}
+
 /// Get the ip of a Node.
 pub fn node_ip(node: &Node) -> Option<String> {
     node.status
@@ -493,6 +1579,8 @@ pub fn node_ip(node: &Node) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     // use k8s_openapi::api::core::v1::{Container, PodSpec};
 
     // use super::*;
@@ -572,4 +1660,98 @@ mod tests {
 
     //     c.pod_delete("default", "exec-test").await.unwrap();
     // }
+
+    fn q(s: &str) -> Quantity {
+        Quantity(s.to_string())
+    }
+
+    /// Assert that a parsed quantity equals `expected_numerator /
+    /// expected_denominator`, without requiring the same (unreduced) form.
+    fn assert_quantity_eq(input: &str, expected_numerator: i128, expected_denominator: i128) {
+        let (n, d) = parse_quantity_rational(&q(input)).unwrap();
+        assert_eq!(
+            n * expected_denominator,
+            expected_numerator * d,
+            "quantity {} parsed as {}/{}, expected {}/{}",
+            input,
+            n,
+            d,
+            expected_numerator,
+            expected_denominator
+        );
+    }
+
+    #[test]
+    fn test_parse_quantity_decimal_suffixes() {
+        assert_quantity_eq("0", 0, 1);
+        assert_quantity_eq("100", 100, 1);
+        assert_quantity_eq("100n", 1, 10_000_000);
+        assert_quantity_eq("100u", 1, 10_000);
+        assert_quantity_eq("250m", 1, 4);
+        assert_quantity_eq("1k", 1_000, 1);
+        assert_quantity_eq("1M", 1_000_000, 1);
+        assert_quantity_eq("1G", 1_000_000_000, 1);
+        assert_quantity_eq("1T", 1_000_000_000_000, 1);
+        assert_quantity_eq("1P", 1_000_000_000_000_000, 1);
+        assert_quantity_eq("1E", 1_000_000_000_000_000_000, 1);
+    }
+
+    #[test]
+    fn test_parse_quantity_binary_suffixes() {
+        assert_quantity_eq("1Ki", 1024, 1);
+        assert_quantity_eq("1Mi", 1024i128.pow(2), 1);
+        assert_quantity_eq("1Gi", 1024i128.pow(3), 1);
+        assert_quantity_eq("1Ti", 1024i128.pow(4), 1);
+        assert_quantity_eq("1Pi", 1024i128.pow(5), 1);
+        assert_quantity_eq("1Ei", 1024i128.pow(6), 1);
+    }
+
+    #[test]
+    fn test_parse_quantity_fractional_and_exponent() {
+        assert_quantity_eq("1.5Gi", 3 * 1024i128.pow(3), 2);
+        assert_quantity_eq("100.5m", 1005, 10_000);
+        assert_quantity_eq("1.5e3", 1_500, 1);
+        assert_quantity_eq("-0.25", -1, 4);
+    }
+
+    #[test]
+    fn test_parse_quantity_errors() {
+        assert!(parse_quantity_rational(&q("")).is_err());
+        assert!(parse_quantity_rational(&q("abc")).is_err());
+        assert!(parse_quantity_rational(&q("1Xi")).is_err());
+    }
+
+    fn metrics_with_usage(usages: &[(&str, &str)]) -> PodMetrics {
+        PodMetrics {
+            metadata: Default::default(),
+            timestamp: chrono::Utc::now(),
+            window: String::new(),
+            containers: usages
+                .iter()
+                .enumerate()
+                .map(|(i, (cpu, memory))| PodMetricsContainer {
+                    name: format!("container-{}", i),
+                    usage: PodMetricsContainerUsage {
+                        cpu: q(cpu),
+                        memory: q(memory),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_pod_metrics_total_cpu_sums_fractional_millicores() {
+        let metrics = metrics_with_usage(&[("250m", "0"), ("0.5", "0")]);
+        assert_eq!(pod_metrics_total_cpu(&metrics).unwrap(), 750);
+    }
+
+    #[test]
+    fn test_pod_metrics_total_memory_sums_binary_suffixes() {
+        let metrics = metrics_with_usage(&[("0", "100Mi"), ("0", "1Gi")]);
+        assert_eq!(
+            pod_metrics_total_memory(&metrics).unwrap(),
+            100 * 1024 * 1024 + 1024 * 1024 * 1024
+        );
+    }
 }
@@ -1,9 +1,11 @@
 //! Application configuration and parsing.
 
-use std::{collections::HashMap, net::SocketAddr};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
 use anyhow::{bail, Context};
-use k8s_openapi::api::core::v1::PodSpec;
+use arc_swap::ArcSwap;
+use k8s_openapi::{api::core::v1::PodSpec, apimachinery::pkg::api::resource::Quantity};
+use kube_quantity::ParsedQuantity;
 
 use crate::AnyError;
 
@@ -28,6 +30,36 @@ pub struct ConfigSource {
     /// Eg: 0.0.0.0:8080 / 127.0.0.1:8080
     pub server_address: Option<String>,
 
+    /// Address for the admin HTTP listener exposing `/live`/`/ready`
+    /// Kubernetes probes, see [`crate::server::run_admin_server`]. Kept
+    /// separate from [`Self::server_address`] so probes stay reachable even
+    /// while `/api/query` is draining during a graceful shutdown.
+    /// Defaults to `0.0.0.0:8081`.
+    pub admin_address: Option<String>,
+
+    /// How long [`crate::server::run_server`] waits for in-flight
+    /// `/api/query` calls to finish draining after receiving
+    /// SIGTERM/SIGINT before forcing the listener closed. Defaults to 20
+    /// seconds.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "20s", "1m"
+    #[serde(default, with = "humantime_serde::option")]
+    pub shutdown_grace_period: Option<std::time::Duration>,
+
+    /// Path to a PEM-encoded TLS certificate (chain) for the API server.
+    /// Must be set together with [`Self::tls_key_path`] to enable HTTPS -
+    /// when either is missing the server falls back to plain HTTP.
+    pub tls_cert_path: Option<String>,
+    /// Path to the PEM-encoded private key matching [`Self::tls_cert_path`].
+    pub tls_key_path: Option<String>,
+
+    /// Shared pre-shared key used to authenticate the calling frontend
+    /// itself via HMAC-signed `/api/query`, `/api/logs` and `/api/watch`
+    /// requests (see
+    /// [`crate::server::verify_signature`]). `None` disables request
+    /// signing entirely.
+    pub request_signing_psk: Option<String>,
+
     pub prometheus_exporter: Option<ConfigSourcePrometheusExporter>,
 
     /// The namespace where user volumes and workspace pods are created.
@@ -39,6 +71,30 @@ pub struct ConfigSource {
     #[serde(default)]
     pub users: Vec<User>,
 
+    /// Where to source the workspace user whitelist from at runtime, see
+    /// [`crate::user_provider`]. Defaults to [`UsersProviderConfig::Static`]
+    /// (ie. just [`Self::users`]).
+    pub users_provider: Option<UsersProviderConfig>,
+
+    /// If true (the default), the operator installs the `WorkspaceUser`
+    /// CustomResourceDefinition on startup if it isn't already present, see
+    /// [`crate::user_provider::CrdUserProvider`]. Set to false if the CRD is
+    /// managed out-of-band (eg. by a Helm chart/GitOps pipeline) and the
+    /// operator's service account shouldn't need CRD-write RBAC.
+    pub auto_register_user_crd: Option<bool>,
+
+    /// If true (the default), the operator installs the `Workspace`
+    /// CustomResourceDefinition on startup if it isn't already present, see
+    /// [`crate::operator::workspace_controller`]. Set to false if the CRD is
+    /// managed out-of-band (eg. by a Helm chart/GitOps pipeline) and the
+    /// operator's service account shouldn't need CRD-write RBAC.
+    pub auto_register_workspace_crd: Option<bool>,
+
+    /// Which [`crate::auth::AuthBackend`] authenticates `/api/query`
+    /// callers. Defaults to [`AuthConfig::SshWhitelist`] (the original
+    /// behavior).
+    pub auth: Option<AuthConfig>,
+
     /// Maximum size for user /home volumes.
     /// Also used as the default value.
     pub max_home_volume_size: Option<String>,
@@ -48,8 +104,95 @@ pub struct ConfigSource {
     pub pod_template: Option<PodSpec>,
     /// The Kubernetes storage class to for the user /home volumes.
     pub storage_class: Option<String>,
+    /// How a user's workspace compute is provisioned. Defaults to
+    /// [`WorkspaceBackend::Pod`] (the original behavior).
+    pub workspace_backend: Option<WorkspaceBackend>,
 
     pub auto_shutdown: Option<AutoShutdown>,
+
+    /// Maximum time to wait for a newly-provisioned workspace pod to become
+    /// ready, eg. in [`crate::operator::Operator::wait_until_ready`].
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "2 minutes", "30s"
+    #[serde(default, with = "humantime_serde::option")]
+    pub pod_ready_timeout: Option<std::time::Duration>,
+
+    /// Periodic home-volume backup configuration, see
+    /// [`crate::operator::Operator::ensure_user_backup_cronjob`].
+    pub backup: Option<BackupConfig>,
+
+    /// If true, the home-directory `PersistentVolumeClaim` of a removed
+    /// user is also deleted during orphan garbage collection. If false
+    /// (the default), only the Pod/StatefulSet/Service/backup CronJob are
+    /// cleaned up and the PVC is
+    /// retained so it can be recovered by re-adding the user.
+    pub gc_reclaim_orphaned_volumes: Option<bool>,
+
+    /// Timeouts applied around individual Kubernetes API operations, so a
+    /// hung request (eg. an exec session against an unresponsive pod)
+    /// can't block the operator indefinitely.
+    pub timeouts: Option<ConfigSourceTimeouts>,
+
+    /// Registry-digest-driven workspace image auto-update, see
+    /// [`crate::operator::Operator::check_image_updates`].
+    pub autoupdate: Option<AutoUpdateConfig>,
+
+    /// How often [`crate::operator::Operator::run_loop`] runs its recurring
+    /// checks (namespace/orphan/image-update). Defaults to 30 seconds.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "30s", "2m"
+    #[serde(default, with = "humantime_serde::option")]
+    pub check_interval: Option<std::time::Duration>,
+
+    /// Readiness probe timing applied to workspace pods' SSH port check,
+    /// see [`crate::operator::Operator::build_user_pod_spec`].
+    pub pod_readiness_probe: Option<ConfigSourcePodReadinessProbe>,
+
+    /// How a workspace pod's SSH access is provisioned on cold start, see
+    /// [`crate::operator::Operator::user_pod_command`]. Defaults to
+    /// [`SshProvisioningMode::AptGet`] (the original `apt-get
+    /// install openssh-server` behavior) with no init container.
+    pub ssh_provisioning: Option<ConfigSourceSshProvisioning>,
+}
+
+/// See [`ConfigSource::ssh_provisioning`].
+#[derive(serde::Deserialize, Default, Debug)]
+pub struct ConfigSourceSshProvisioning {
+    pub mode: Option<SshProvisioningMode>,
+    /// If true, the home-volume permission setup (creating `.ssh`, fixing
+    /// ownership) runs in a dedicated init container ahead of the main
+    /// container, instead of inline as part of its startup command. Has no
+    /// effect with [`SshProvisioningMode::Custom`], which is responsible
+    /// for its own setup. Defaults to false.
+    pub use_init_container: Option<bool>,
+}
+
+/// See [`ConfigSource::pod_readiness_probe`].
+#[derive(serde::Deserialize, Default, Debug)]
+pub struct ConfigSourcePodReadinessProbe {
+    /// Delay before the first readiness probe is run against a newly
+    /// created pod. Defaults to 60 seconds.
+    /// Format: all formats supported by the humantime crate.
+    #[serde(default, with = "humantime_serde::option")]
+    pub initial_delay: Option<std::time::Duration>,
+    /// How often the readiness probe is repeated. Defaults to 30 seconds.
+    /// Format: all formats supported by the humantime crate.
+    #[serde(default, with = "humantime_serde::option")]
+    pub period: Option<std::time::Duration>,
+}
+
+/// See [`ConfigSource::timeouts`].
+#[derive(serde::Deserialize, Default, Debug)]
+pub struct ConfigSourceTimeouts {
+    /// Maximum time to wait for a `pod exec` call (eg. checking TCP
+    /// connection counts for autoshutdown) to complete.
+    /// Format: all formats supported by the humantime crate.
+    #[serde(default, with = "humantime_serde::option")]
+    pub pod_exec: Option<std::time::Duration>,
+    /// Maximum time to wait for a Pod creation call to complete.
+    /// Format: all formats supported by the humantime crate.
+    #[serde(default, with = "humantime_serde::option")]
+    pub pod_create: Option<std::time::Duration>,
 }
 
 impl ConfigSource {
@@ -58,7 +201,7 @@ impl ConfigSource {
     pub fn load_from_env() -> Result<Config, AnyError> {
         let vars: HashMap<String, String> = std::env::vars().collect();
 
-        let file_config: ConfigSource = if let Some(path) = vars.get(ENV_VAR_CONFIG_PATH) {
+        let mut file_config: ConfigSource = if let Some(path) = vars.get(ENV_VAR_CONFIG_PATH) {
             tracing::trace!(path=%path, "loading config file");
             let content = std::fs::read(&path).context("Could not read config file")?;
             serde_json::from_slice(&content).context("Could not deserialize config")?
@@ -66,33 +209,152 @@ impl ConfigSource {
             ConfigSource::default()
         };
 
-        // TODO: parse individual settings from individual env vars
-        // ( KUBE_WORKSPACE_* )
+        // Environment overrides layer on top of the file (if any), so a
+        // Deployment manifest can tweak settings via plain env vars without
+        // having to mount/template a full JSON file.
+        file_config.apply_env_overrides(&vars)?;
+
         let server_address = file_config.server_address;
+        let admin_address = file_config.admin_address;
+        let shutdown_grace_period = file_config.shutdown_grace_period;
+        let tls_cert_path = file_config.tls_cert_path;
+        let tls_key_path = file_config.tls_key_path;
+        let request_signing_psk = file_config.request_signing_psk;
         let namespace = file_config.namespace;
         let auto_create_namespace = file_config.auto_create_namespace;
         let users = file_config.users;
+        let users_provider = file_config.users_provider;
+        let auto_register_user_crd = file_config.auto_register_user_crd;
+        let auto_register_workspace_crd = file_config.auto_register_workspace_crd;
+        let auth = file_config.auth;
         let max_home_volume_size = file_config.max_home_volume_size;
         let pod_template = file_config.pod_template;
         let storage_class = file_config.storage_class;
+        let workspace_backend = file_config.workspace_backend;
         let auto_shutdown = file_config.auto_shutdown;
         let prometheus_exporter = file_config.prometheus_exporter;
+        let pod_ready_timeout = file_config.pod_ready_timeout;
+        let backup = file_config.backup;
+        let gc_reclaim_orphaned_volumes = file_config.gc_reclaim_orphaned_volumes;
+        let timeouts = file_config.timeouts;
+        let autoupdate = file_config.autoupdate;
+        let check_interval = file_config.check_interval;
+        let pod_readiness_probe = file_config.pod_readiness_probe;
+        let ssh_provisioning = file_config.ssh_provisioning;
 
         let source = Self {
             server_address,
+            admin_address,
+            shutdown_grace_period,
+            tls_cert_path,
+            tls_key_path,
+            request_signing_psk,
             prometheus_exporter,
             namespace,
             auto_create_namespace,
             users,
+            users_provider,
+            auto_register_user_crd,
+            auto_register_workspace_crd,
+            auth,
             max_home_volume_size,
             pod_template,
             storage_class,
+            workspace_backend,
             auto_shutdown,
+            pod_ready_timeout,
+            backup,
+            gc_reclaim_orphaned_volumes,
+            timeouts,
+            autoupdate,
+            check_interval,
+            pod_readiness_probe,
+            ssh_provisioning,
         };
 
         source.build()
     }
 
+    /// Layer `KUBE_WORKSPACE_*` environment variables on top of the values
+    /// already loaded from the config file (if any), so a Deployment
+    /// manifest can tweak a setting via plain env vars without having to
+    /// mount/template a full JSON file. Only the plain top-level scalars
+    /// (plus one representative flag each for
+    /// [`Self::prometheus_exporter`]/[`Self::auto_shutdown`]) are covered;
+    /// anything more structured (eg. the auto-shutdown thresholds,
+    /// `pod_template`) is still config-file-only.
+    fn apply_env_overrides(&mut self, vars: &HashMap<String, String>) -> Result<(), AnyError> {
+        fn parse<T>(vars: &HashMap<String, String>, key: &str) -> Result<Option<T>, AnyError>
+        where
+            T: std::str::FromStr,
+            T::Err: std::fmt::Display,
+        {
+            vars.get(key)
+                .map(|raw| raw.parse().map_err(|err| anyhow::anyhow!("Invalid value for {key}: {err}")))
+                .transpose()
+        }
+
+        fn parse_duration(vars: &HashMap<String, String>, key: &str) -> Result<Option<std::time::Duration>, AnyError> {
+            vars.get(key)
+                .map(|raw| humantime::parse_duration(raw).map_err(|err| anyhow::anyhow!("Invalid value for {key}: {err}")))
+                .transpose()
+        }
+
+        if let Some(v) = vars.get("KUBE_WORKSPACE_SERVER_ADDRESS") {
+            self.server_address = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_ADMIN_ADDRESS") {
+            self.admin_address = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_TLS_CERT_PATH") {
+            self.tls_cert_path = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_TLS_KEY_PATH") {
+            self.tls_key_path = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_REQUEST_SIGNING_PSK") {
+            self.request_signing_psk = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_NAMESPACE") {
+            self.namespace = Some(v.clone());
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_AUTO_CREATE_NAMESPACE")? {
+            self.auto_create_namespace = Some(v);
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_AUTO_REGISTER_USER_CRD")? {
+            self.auto_register_user_crd = Some(v);
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_AUTO_REGISTER_WORKSPACE_CRD")? {
+            self.auto_register_workspace_crd = Some(v);
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_MAX_HOME_VOLUME_SIZE") {
+            self.max_home_volume_size = Some(v.clone());
+        }
+        if let Some(v) = vars.get("KUBE_WORKSPACE_STORAGE_CLASS") {
+            self.storage_class = Some(v.clone());
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_GC_RECLAIM_ORPHANED_VOLUMES")? {
+            self.gc_reclaim_orphaned_volumes = Some(v);
+        }
+        if let Some(v) = parse_duration(vars, "KUBE_WORKSPACE_SHUTDOWN_GRACE_PERIOD")? {
+            self.shutdown_grace_period = Some(v);
+        }
+        if let Some(v) = parse_duration(vars, "KUBE_WORKSPACE_POD_READY_TIMEOUT")? {
+            self.pod_ready_timeout = Some(v);
+        }
+        if let Some(v) = parse_duration(vars, "KUBE_WORKSPACE_CHECK_INTERVAL")? {
+            self.check_interval = Some(v);
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_PROMETHEUS_EXPORTER_ENABLED")? {
+            self.prometheus_exporter.get_or_insert_with(Default::default).enabled = Some(v);
+        }
+        if let Some(v) = parse(vars, "KUBE_WORKSPACE_AUTO_SHUTDOWN_ENABLE")? {
+            self.auto_shutdown.get_or_insert_with(Default::default).enable = v;
+        }
+
+        Ok(())
+    }
+
     /// Convert into a [`Config`] by setting default values.
     fn build(self) -> Result<Config, anyhow::Error> {
         let server_address: SocketAddr = self
@@ -101,6 +363,20 @@ impl ConfigSource {
             .parse()
             .context("Invalid server address")?;
 
+        let admin_address: SocketAddr = self
+            .admin_address
+            .unwrap_or_else(|| "0.0.0.0:8081".to_string())
+            .parse()
+            .context("Invalid admin address")?;
+
+        let tls = match (self.tls_cert_path, self.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(ServerTls { cert_path, key_path }),
+            (None, None) => None,
+            (Some(_), None) | (None, Some(_)) => {
+                bail!("tls_cert_path and tls_key_path must both be set to enable TLS")
+            }
+        };
+
         let prometheus_exporter = if let Some(p) = self.prometheus_exporter {
             if p.enabled.unwrap_or(false) {
                 let address = p
@@ -126,12 +402,22 @@ impl ConfigSource {
 
         let c = Config {
             server_address,
+            admin_address,
+            shutdown_grace_period: self
+                .shutdown_grace_period
+                .unwrap_or(std::time::Duration::from_secs(20)),
+            tls,
+            request_signing_psk: self.request_signing_psk,
             namespace: self
                 .namespace
                 .map(|x| x.trim().to_string())
                 .unwrap_or_else(|| "kube-workspaces".to_string()),
             auto_create_namespace: self.auto_create_namespace.unwrap_or(true),
             users: self.users,
+            users_provider: self.users_provider.unwrap_or(UsersProviderConfig::Static),
+            auto_register_user_crd: self.auto_register_user_crd.unwrap_or(true),
+            auto_register_workspace_crd: self.auto_register_workspace_crd.unwrap_or(true),
+            auth: self.auth.unwrap_or(AuthConfig::SshWhitelist),
             max_home_volume_size: self
                 .max_home_volume_size
                 .unwrap_or_else(|| "10Gi".to_string()),
@@ -139,11 +425,47 @@ impl ConfigSource {
                 ..Default::default()
             }),
             storage_class: self.storage_class,
+            workspace_backend: self.workspace_backend.unwrap_or(WorkspaceBackend::Pod),
             auto_shutdown: self.auto_shutdown.unwrap_or(AutoShutdown {
                 enable: false,
                 cpu_usage: None,
+                memory_usage: None,
                 tcp_idle: None,
+                match_policy: IdleMatchPolicy::All,
+                shutdown_grace_period: std::time::Duration::default(),
+                warning_webhook: None,
+                metrics_staleness_threshold: AutoShutdown::default_metrics_staleness_threshold(),
             }),
+            pod_ready_timeout: self
+                .pod_ready_timeout
+                .unwrap_or(std::time::Duration::from_secs(120)),
+            backup: self.backup,
+            gc_reclaim_orphaned_volumes: self.gc_reclaim_orphaned_volumes.unwrap_or(false),
+            check_interval: self
+                .check_interval
+                .unwrap_or(std::time::Duration::from_secs(30)),
+            pod_readiness_probe: {
+                let p = self.pod_readiness_probe.unwrap_or_default();
+                ConfigPodReadinessProbe {
+                    initial_delay: p.initial_delay.unwrap_or(std::time::Duration::from_secs(60)),
+                    period: p.period.unwrap_or(std::time::Duration::from_secs(30)),
+                }
+            },
+            ssh_provisioning: {
+                let p = self.ssh_provisioning.unwrap_or_default();
+                SshProvisioning {
+                    mode: p.mode.unwrap_or(SshProvisioningMode::AptGet),
+                    use_init_container: p.use_init_container.unwrap_or(false),
+                }
+            },
+            timeouts: {
+                let t = self.timeouts.unwrap_or_default();
+                ConfigTimeouts {
+                    pod_exec: t.pod_exec.unwrap_or(std::time::Duration::from_secs(30)),
+                    pod_create: t.pod_create.unwrap_or(std::time::Duration::from_secs(30)),
+                }
+            },
+            autoupdate: self.autoupdate,
             prometheus_exporter,
         };
 
@@ -160,6 +482,19 @@ pub struct Config {
     /// Port where the API server should run.
     pub server_address: std::net::SocketAddr,
 
+    /// See [`ConfigSource::admin_address`].
+    pub admin_address: std::net::SocketAddr,
+
+    /// See [`ConfigSource::shutdown_grace_period`].
+    pub shutdown_grace_period: std::time::Duration,
+
+    /// TLS cert/key pair to terminate HTTPS at the API server. `None` serves
+    /// plain HTTP, eg. for local/dev deployments behind a trusted network.
+    pub tls: Option<ServerTls>,
+
+    /// See [`ConfigSource::request_signing_psk`].
+    pub request_signing_psk: Option<String>,
+
     pub prometheus_exporter: Option<ConfigPrometheusExporter>,
 
     /// The namespace where user volumes and workspace pods are created.
@@ -169,6 +504,14 @@ pub struct Config {
     pub auto_create_namespace: bool,
     /// The user whitelist that is allowed to create containers.
     pub users: Vec<User>,
+    /// See [`ConfigSource::users_provider`].
+    pub users_provider: UsersProviderConfig,
+    /// See [`ConfigSource::auto_register_user_crd`].
+    pub auto_register_user_crd: bool,
+    /// See [`ConfigSource::auto_register_workspace_crd`].
+    pub auto_register_workspace_crd: bool,
+    /// See [`ConfigSource::auth`].
+    pub auth: AuthConfig,
 
     /// Maximum size for user /home volumes.
     /// Also used as the default value.
@@ -179,8 +522,51 @@ pub struct Config {
     pub pod_template: PodSpec,
     /// The Kubernetes storage class to for the user /home volumes.
     pub storage_class: Option<String>,
+    /// See [`ConfigSource::workspace_backend`].
+    pub workspace_backend: WorkspaceBackend,
 
     pub auto_shutdown: AutoShutdown,
+
+    /// Maximum time to wait for a newly-provisioned workspace pod to become
+    /// ready, see [`crate::operator::Operator::wait_until_ready`].
+    pub pod_ready_timeout: std::time::Duration,
+
+    /// Periodic home-volume backup configuration.
+    pub backup: Option<BackupConfig>,
+
+    /// If true, orphan garbage collection also deletes the home-directory
+    /// PVC of a removed user, rather than just its Pod/Service.
+    pub gc_reclaim_orphaned_volumes: bool,
+
+    /// Timeouts applied around individual Kubernetes API operations.
+    pub timeouts: ConfigTimeouts,
+
+    /// Registry-digest-driven workspace image auto-update. `None` disables
+    /// the subsystem entirely regardless of any per-pod opt-in annotation.
+    pub autoupdate: Option<AutoUpdateConfig>,
+
+    /// See [`ConfigSource::check_interval`].
+    pub check_interval: std::time::Duration,
+
+    /// See [`ConfigSource::pod_readiness_probe`].
+    pub pod_readiness_probe: ConfigPodReadinessProbe,
+
+    /// See [`ConfigSource::ssh_provisioning`].
+    pub ssh_provisioning: SshProvisioning,
+}
+
+/// Resolved readiness probe timing, see [`ConfigSourcePodReadinessProbe`].
+#[derive(Clone, Debug)]
+pub struct ConfigPodReadinessProbe {
+    pub initial_delay: std::time::Duration,
+    pub period: std::time::Duration,
+}
+
+/// Resolved timeouts, see [`ConfigSourceTimeouts`].
+#[derive(Clone, Debug)]
+pub struct ConfigTimeouts {
+    pub pod_exec: std::time::Duration,
+    pub pod_create: std::time::Duration,
 }
 
 #[derive(Clone, Debug)]
@@ -189,11 +575,20 @@ pub struct ConfigPrometheusExporter {
     pub auto_register_operator_service_monitor: bool,
 }
 
+/// Resolved TLS cert/key pair, see [`ConfigSource::tls_cert_path`].
+#[derive(Clone, Debug)]
+pub struct ServerTls {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
 impl Config {
     /// Check if autoshutdown is enabled.
     pub fn autoshutdown_enabled(&self) -> bool {
         self.auto_shutdown.enable
-            && (self.auto_shutdown.tcp_idle.is_some() || self.auto_shutdown.cpu_usage.is_some())
+            && (self.auto_shutdown.tcp_idle.is_some()
+                || self.auto_shutdown.cpu_usage.is_some()
+                || self.auto_shutdown.memory_usage.is_some())
     }
 
     /// Verify that a username and SSH public key pair are in the configured
@@ -223,8 +618,100 @@ impl Config {
             bail!("Namespace may not be an empty string");
         }
 
+        Self::parse_storage_quantity("max_home_volume_size", &self.max_home_volume_size)?;
+        for user in &self.users {
+            if let Some(size) = &user.home_volume_size {
+                Self::parse_storage_quantity(
+                    &format!("users.{}.home_volume_size", user.username),
+                    size,
+                )?;
+            }
+        }
+
         Ok(())
     }
+
+    /// Parse a Kubernetes storage quantity string (eg. `"10Gi"`), rejecting
+    /// malformed values up front with a clear error instead of letting them
+    /// surface as an opaque API rejection at apply time.
+    pub(crate) fn parse_storage_quantity(label: &str, raw: &str) -> Result<ParsedQuantity, AnyError> {
+        ParsedQuantity::try_from(Quantity(raw.to_string()))
+            .map_err(|err| anyhow::anyhow!("Invalid storage quantity for {}: '{}' ({})", label, raw, err))
+    }
+
+    /// Resolve the effective home-volume storage size for `user`, clamping
+    /// any per-user override down to the configured maximum by numeric
+    /// comparison (rather than just handing both straight to Kubernetes and
+    /// letting quota enforcement happen server-side).
+    pub fn home_volume_size_for(&self, user: &User) -> Result<String, AnyError> {
+        let max = Self::parse_storage_quantity("max_home_volume_size", &self.max_home_volume_size)?;
+
+        let requested = match &user.home_volume_size {
+            Some(size) => size,
+            None => return Ok(self.max_home_volume_size.clone()),
+        };
+
+        let parsed = Self::parse_storage_quantity(
+            &format!("users.{}.home_volume_size", user.username),
+            requested,
+        )?;
+
+        if parsed > max {
+            tracing::warn!(
+                user = %user.username,
+                requested = %requested,
+                max = %self.max_home_volume_size,
+                "requested home volume size exceeds the configured maximum, clamping"
+            );
+            Ok(self.max_home_volume_size.clone())
+        } else {
+            Ok(requested.clone())
+        }
+    }
+}
+
+/// Periodic home-volume backup configuration, applied per-user via a
+/// `batch/v1::CronJob`.
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct BackupConfig {
+    pub enable: bool,
+    /// Cron schedule the backup `CronJob` runs on.
+    /// EG: "0 3 * * *" (daily at 03:00)
+    pub schedule: String,
+    /// Number of completed backup Jobs to keep around, for the CronJob's
+    /// `successfulJobsHistoryLimit`.
+    #[serde(default = "BackupConfig::default_retention")]
+    pub retention: i32,
+    /// Name of the `PersistentVolumeClaim` that backups are written to.
+    /// Must already exist in the operator's namespace.
+    pub target_volume_claim: String,
+    /// Image used to run the backup command.
+    #[serde(default = "BackupConfig::default_image")]
+    pub image: String,
+}
+
+impl BackupConfig {
+    fn default_retention() -> i32 {
+        7
+    }
+
+    fn default_image() -> String {
+        "ubuntu".to_string()
+    }
+}
+
+/// Registry-digest-driven workspace image auto-update. Disabled by default,
+/// and even when enabled only applies to pods carrying the
+/// `kube-workspaces.foundational.cc/autoupdate: registry` annotation - see
+/// [`crate::operator::Operator::check_image_updates`].
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct AutoUpdateConfig {
+    pub enable: bool,
+    /// Minimum time between registry digest checks for a given pod.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "1h", "30m"
+    #[serde(with = "humantime_serde")]
+    pub check_interval: std::time::Duration,
 }
 
 /// Automatic container shutdown configuration.
@@ -232,7 +719,59 @@ impl Config {
 pub struct AutoShutdown {
     pub enable: bool,
     pub cpu_usage: Option<CpuIdleAutoShutown>,
+    pub memory_usage: Option<MemoryIdleAutoShutdown>,
     pub tcp_idle: Option<TcpIdleAutoShutdown>,
+    /// How configured idle signals are combined to decide if a pod is idle.
+    /// Defaults to [`IdleMatchPolicy::All`], matching this feature's
+    /// pre-existing (implicit) behavior.
+    #[serde(default)]
+    pub match_policy: IdleMatchPolicy,
+    /// How long a pod stays in the shutdown warning phase (see
+    /// [`crate::operator::PodMetricsAnnotion::shutdown_decision`]) before it
+    /// is actually torn down, giving the user a window to resume activity
+    /// and cancel the shutdown. Defaults to zero, ie. shutting down
+    /// immediately once idle.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "10m", "1h"
+    #[serde(with = "humantime_serde", default)]
+    pub shutdown_grace_period: std::time::Duration,
+    /// Optional webhook URL POSTed to when a workspace enters the shutdown
+    /// warning phase, eg. so a chat bot can notify the user.
+    pub warning_webhook: Option<String>,
+    /// How long a pod's idle-tracking annotation is trusted before being
+    /// considered stale and reset, see
+    /// [`crate::operator::Operator::analyze_pod_autoshutdown`]. Defaults to
+    /// 5 minutes.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "5m", "90s"
+    #[serde(with = "humantime_serde", default = "AutoShutdown::default_metrics_staleness_threshold")]
+    pub metrics_staleness_threshold: std::time::Duration,
+}
+
+impl AutoShutdown {
+    fn default_metrics_staleness_threshold() -> std::time::Duration {
+        std::time::Duration::from_secs(5 * 60)
+    }
+}
+
+/// How the configured idle signals ([`AutoShutdown::cpu_usage`],
+/// [`AutoShutdown::memory_usage`], [`AutoShutdown::tcp_idle`]) are combined
+/// to decide whether a pod counts as idle.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleMatchPolicy {
+    /// Every configured (and currently active) signal must have exceeded
+    /// its own `minimum_idle_time` before the pod is considered idle.
+    All,
+    /// The pod is considered idle as soon as any one configured signal has
+    /// exceeded its own `minimum_idle_time`.
+    Any,
+}
+
+impl Default for IdleMatchPolicy {
+    fn default() -> Self {
+        Self::All
+    }
 }
 
 /// Automatic container shutdown configuration.
@@ -251,18 +790,126 @@ pub struct CpuIdleAutoShutown {
     pub cpu_threshold: u64,
 }
 
-/// Configure auto-shutdown of containers when no tcp connections are detected.
+/// Configure auto-shutdown of containers whose memory usage is idle, eg. to
+/// catch pods that are CPU-idle but pinned by a memory leak.
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct MemoryIdleAutoShutdown {
+    /// Minimum time that the pod needs to be below the specified memory
+    /// usage threshold.
+    /// Format: all formats supported by the humantime crate.
+    /// EG: "2 hours", "1d", "5 hours 20m"
+    #[serde(with = "humantime_serde")]
+    pub minimum_idle_time: std::time::Duration,
+    /// Memory usage threshold, in bytes, that is considered idle.
+    pub memory_threshold: u64,
+}
+
+/// Configure auto-shutdown of containers whose network activity is idle.
+///
+/// Idle-ness is measured as a combined rx+tx byte rate sampled from the
+/// node's kubelet `/stats/summary` endpoint, see
+/// [`crate::operator::Operator::sample_network_idle`].
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct TcpIdleAutoShutdown {
-    /// Minimum number of seconds before idle shutdown takes effect.
+    /// Minimum time the byte rate needs to stay below `byte_rate_threshold`
+    /// before idle shutdown takes effect.
     /// Format: all formats supported by the humantime crate.
     /// EG: "2 hours", "1d", "5 hours 20m"
     #[serde(with = "humantime_serde")]
     pub minimum_idle_time: std::time::Duration,
-    /// TCP ports to ignore.
-    pub ignored_ports: Vec<u16>,
+    /// Combined rx+tx byte rate (bytes/second) at or below which the pod is
+    /// considered network-idle.
+    pub byte_rate_threshold: u64,
+}
+
+/// Selects how a user's workspace compute is provisioned, see
+/// [`crate::operator::Operator::ensure_user_workspace`].
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WorkspaceBackend {
+    /// The original behavior: a bare `Pod`, recreated from scratch on every
+    /// restart - see [`crate::operator::Operator::ensure_user_pod`].
+    Pod,
+    /// A single-replica `StatefulSet`, giving the workspace a stable
+    /// identity/volume that survives pod rescheduling - see
+    /// [`crate::operator::Operator::ensure_user_statefulset`].
+    StatefulSet,
 }
 
+/// Selects how a workspace pod's main container gets its `sshd` running,
+/// see [`crate::operator::Operator::user_pod_command`].
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SshProvisioningMode {
+    /// Install `openssh-server` via `apt-get` on every cold start. Simple,
+    /// but slow and only works on Debian-based images.
+    AptGet,
+    /// Skip package installation - the image's `pod_template` already
+    /// ships `sshd` - and only create the user and start the service.
+    Prebaked,
+    /// Run a custom shell command instead of the built-in setup, with
+    /// `{username}`/`{ssh_public_key}` placeholders substituted in. Lets
+    /// an arbitrary base image (eg. non-Debian, or air-gapped with no
+    /// package registry reachable) provision itself however it needs to.
+    Custom { command: String },
+}
+
+/// Resolved form of [`ConfigSource::ssh_provisioning`].
+#[derive(Clone, Debug)]
+pub struct SshProvisioning {
+    pub mode: SshProvisioningMode,
+    pub use_init_container: bool,
+}
+
+/// Selects which [`crate::user_provider::UserProvider`] backs the workspace
+/// user whitelist.
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UsersProviderConfig {
+    /// Source users from [`ConfigSource::users`], frozen at process start.
+    Static,
+    /// Source users from a `users(username, ssh_public_key, home_volume_size)` table reachable
+    /// at `url` (any `sqlx::Any`-supported database, eg. Postgres/SQLite).
+    Sql { url: String },
+}
+
+/// Selects which [`crate::auth::AuthBackend`] authenticates `/api/query`
+/// callers, see [`ConfigSource::auth`].
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuthConfig {
+    /// The original behavior: match a username to the SSH public key on
+    /// file for it, see [`UsersProviderConfig`]/[`crate::user_provider`].
+    SshWhitelist,
+    /// Trust a configured SSO issuer: callers authenticate with a bearer
+    /// token instead of a pre-registered SSH key, see
+    /// [`crate::auth::OidcBackend`].
+    Oidc {
+        /// The OIDC issuer URL, eg. `https://accounts.example.com`. Its
+        /// `/.well-known/openid-configuration` document is used to
+        /// discover the JWKS endpoint bearer tokens are verified against.
+        issuer: String,
+        /// Expected `aud` claim.
+        audience: String,
+        /// Claim mapped to the workspace username. Defaults to
+        /// `preferred_username`.
+        #[serde(default = "AuthConfig::default_username_claim")]
+        username_claim: String,
+    },
+}
+
+impl AuthConfig {
+    fn default_username_claim() -> String {
+        "preferred_username".to_string()
+    }
+}
+
+/// Hot-swappable handle to the operator's live [`Config`], see
+/// [`crate::operator::config_reload`]. [`crate::operator::Operator::config`]
+/// and the API server read through this on every request instead of
+/// holding a snapshot frozen at process start.
+pub type SharedConfig = Arc<ArcSwap<Config>>;
+
 pub type Username = String;
 
 /// A single configured/whitelisted user account.
@@ -270,4 +917,8 @@ pub type Username = String;
 pub struct User {
     pub username: Username,
     pub ssh_public_key: String,
+    /// Per-user override for the home volume storage size (eg. `"20Gi"`).
+    /// Clamped to [`Config::max_home_volume_size`] if it exceeds it. Falls
+    /// back to the configured maximum if unset.
+    pub home_volume_size: Option<String>,
 }
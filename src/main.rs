@@ -3,10 +3,15 @@
 //! Kubernetes operator that creates workspace pods for users.
 //! Workspaces are started and stopped via API calls exposed at /api/query.
 
+mod auth;
 mod client;
 mod config;
 mod operator;
+mod prometheus;
 mod server;
+mod user_provider;
+mod workspace;
+mod workspace_user;
 
 pub(crate) type AnyError = anyhow::Error;
 
@@ -40,7 +45,21 @@ fn main() {
     let res = rt.block_on(async move {
         // Launch the operator.
         let op = operator::Operator::launch(config.clone()).await?;
-        // Run the webserver.
+
+        // Run the Prometheus metrics exporter, if configured.
+        if let Some(exporter_config) = &config.prometheus_exporter {
+            tokio::task::spawn(prometheus::run_exporter_service(
+                op.metrics().clone(),
+                exporter_config.address,
+            ));
+        }
+
+        // Run the admin server (liveness/readiness probes) separately from
+        // the main API listener, so probes stay reachable even while the
+        // latter is draining for a graceful shutdown.
+        tokio::task::spawn(server::run_admin_server(op.clone()));
+
+        // Run the webserver. Blocks until a graceful shutdown completes.
         server::run_server(op).await;
         Result::<_, AnyError>::Ok(())
     });
@@ -1,7 +1,14 @@
 //! The Kubernetes operator that handles all interaction with a cluster.
 
 mod autoshutdown;
+mod config_reload;
+mod reconciler;
+mod registry;
 mod types;
+mod worker;
+mod workspace_controller;
+
+pub use self::worker::{WorkerInfo, WorkerRegistry, WorkerStatus};
 
 use std::{collections::BTreeMap, iter::FromIterator, sync::Arc};
 
@@ -11,21 +18,41 @@ use self::types::WorkspaceStatus;
 use anyhow::{anyhow, Context};
 use client::PodMetrics;
 use k8s_openapi::{
-    api::core::v1::{
-        Container, ContainerPort, Namespace, PersistentVolumeClaim, PersistentVolumeClaimSpec,
-        PersistentVolumeClaimVolumeSource, Pod, PodSpec, Probe, ResourceRequirements, Service,
-        ServicePort, ServiceSpec, TCPSocketAction, Volume, VolumeMount,
+    api::{
+        apps::v1::{StatefulSet, StatefulSetSpec},
+        batch::v1::{CronJob, CronJobSpec, JobSpec, JobTemplateSpec},
+        core::v1::{
+            Container, ContainerPort, Namespace, PersistentVolumeClaim, PersistentVolumeClaimSpec,
+            PersistentVolumeClaimVolumeSource, Pod, PodSpec, PodTemplateSpec, Probe,
+            ResourceRequirements, Secret, SecretVolumeSource, Service, ServicePort, ServiceSpec,
+            TCPSocketAction, Volume, VolumeMount,
+        },
     },
     apimachinery::pkg::{
-        api::resource::Quantity, apis::meta::v1::LabelSelector, util::intstr::IntOrString,
+        api::resource::Quantity,
+        apis::meta::v1::{LabelSelector, OwnerReference},
+        util::intstr::IntOrString,
+    },
+    ByteString,
+};
+use kube::{
+    api::{ObjectMeta, Patch},
+    Resource,
+};
+use kube_runtime::wait::await_condition;
+use prometheus_client::{
+    encoding::text::Encode,
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{exponential_buckets, Histogram},
     },
 };
-use kube::api::ObjectMeta;
-use prometheus_client::metrics::gauge::Gauge;
 
 use crate::{
     client::{self, Client},
-    config::{self, Config},
+    config::{self, Config, SharedConfig},
     AnyError,
 };
 
@@ -33,16 +60,102 @@ use crate::{
 pub struct Operator(Arc<State>);
 
 struct State {
-    config: Config,
+    /// Live, hot-swappable config, kept up to date by
+    /// [`config_reload::ConfigReloadWorker`]. Use [`Operator::config`]
+    /// rather than reading this directly.
+    config: SharedConfig,
+    /// The Kubernetes namespace resolved at startup. Deliberately *not*
+    /// sourced from `config` on every read - all of the reconciler/workspace
+    /// watchers are scoped to this namespace at launch, so changing it live
+    /// would require tearing them all down and rebuilding them, which is out
+    /// of scope for config reload.
+    namespace: String,
     client: Client,
     metrics: OperatorMetrics,
+    /// Watch-driven cache of workspace status, kept up to date by
+    /// [`reconciler::spawn`].
+    cache: reconciler::SharedCache,
+    /// Queue of pod names awaiting an autoshutdown reconcile, fed by pod
+    /// watch events and drained by [`reconciler::run_autoshutdown_queue`].
+    autoshutdown_queue: reconciler::ReconcileQueue,
+    /// Supervises the operator's named background workers.
+    workers: worker::WorkerManager,
+    /// Authenticates `/api/query` callers, see [`config::Config::auth`].
+    auth_backend: Box<dyn crate::auth::AuthBackend>,
+    /// Flipped to `true` once [`Operator::launch`] has connected to
+    /// Kubernetes and ensured the configured namespace, and back to
+    /// `false` while draining for a graceful shutdown. Backs the admin
+    /// server's `/ready` probe, see [`crate::server::run_admin_server`].
+    ready: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Label set for the per-user gauges in [`OperatorMetrics`].
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+pub struct UserLabel {
+    pub username: String,
+}
+
+/// Label set for [`OperatorMetrics::api_requests_total`], one series per
+/// `/api/query` variant (`pod_start`, `pod_status`, ...).
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+pub struct QueryLabel {
+    pub query: String,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct OperatorMetrics {
     pub configuration_errors: Gauge,
     pub workspace_available_count: Gauge,
     pub workspace_unavailable_count: Gauge,
+    /// Number of workspace pods currently in [`types::WorkspacePhase::Terminating`].
+    pub workspace_terminating_count: Gauge,
+    /// Number of workspace pods currently in [`types::WorkspacePhase::Unknown`].
+    pub workspace_unknown_count: Gauge,
+    /// Total number of workspace pods started via [`Operator::ensure_user_pod`].
+    pub pod_start_count: Counter,
+    /// Total number of workspaces torn down via
+    /// [`Operator::user_pod_shutdown`] or scaled to zero via
+    /// [`Operator::user_statefulset_shutdown`].
+    pub pod_shutdown_count: Counter,
+    /// Total number of pods deleted by the idle-shutdown check, see
+    /// [`Operator::process_pod_autoshutdown`].
+    pub auto_shutdown_count: Counter,
+    /// Total `/api/query` requests handled, labeled by query variant.
+    pub api_requests_total: Family<QueryLabel, Counter>,
+    /// Total `/api/query` requests that returned an error.
+    pub api_errors_total: Counter,
+    /// Seconds since `cpu_idle_since` was first observed for a user's pod,
+    /// see [`Operator::analyze_pod_autoshutdown`]. Set to `0` while the
+    /// user's pod is not currently CPU-idle.
+    pub cpu_idle_seconds: Family<UserLabel, Gauge>,
+    /// Seconds since `memory_idle_since` was first observed for a user's pod.
+    pub memory_idle_seconds: Family<UserLabel, Gauge>,
+    /// Seconds since `network_idle_since` was first observed for a user's pod.
+    pub network_idle_seconds: Family<UserLabel, Gauge>,
+    /// Time from [`Operator::ensure_user_pod`] starting a pod to it
+    /// reaching [`types::WorkspacePhase::Ready`], see [`Operator::wait_until_ready`].
+    pub pod_ready_latency: Histogram,
+}
+
+impl Default for OperatorMetrics {
+    fn default() -> Self {
+        Self {
+            configuration_errors: Gauge::default(),
+            workspace_available_count: Gauge::default(),
+            workspace_unavailable_count: Gauge::default(),
+            workspace_terminating_count: Gauge::default(),
+            workspace_unknown_count: Gauge::default(),
+            pod_start_count: Counter::default(),
+            pod_shutdown_count: Counter::default(),
+            auto_shutdown_count: Counter::default(),
+            api_requests_total: Family::default(),
+            api_errors_total: Counter::default(),
+            cpu_idle_seconds: Family::default(),
+            memory_idle_seconds: Family::default(),
+            network_idle_seconds: Family::default(),
+            pod_ready_latency: Histogram::new(exponential_buckets(1.0, 2.0, 10)),
+        }
+    }
 }
 
 impl std::fmt::Debug for Operator {
@@ -56,6 +169,10 @@ impl Operator {
     const WORKSPACE_POD_LABEL: &'static str = "workspace-pod";
     const WORKSPACE_POD_LABEL_VALUE: &'static str = "true";
     const POD_MAIN_CONTAINER_NAME: &'static str = "workspace";
+    /// Opt-in annotation gating [`Self::check_image_updates`]; only pods
+    /// carrying this annotation set to `"registry"` are checked, even when
+    /// [`config::Config::autoupdate`] is enabled globally.
+    const IMAGE_AUTOUPDATE_ANNOTATION: &'static str = "kube-workspaces.foundational.cc/autoupdate";
 
     /// Build the pod label applied to all workspace pods.
     pub fn workspace_pod_label() -> (String, String) {
@@ -65,14 +182,30 @@ impl Operator {
         )
     }
 
-    /// Get a reference to the operator's config.
+    /// Build the full set of labels applied to a user's workspace pod.
+    fn workspace_pod_labels(user: &config::User) -> BTreeMap<String, String> {
+        BTreeMap::from_iter(vec![
+            (
+                Self::WORKSPACE_POD_LABEL.to_string(),
+                Self::WORKSPACE_POD_LABEL_VALUE.to_string(),
+            ),
+            (
+                Self::WORKSPACE_USER_LABEL.to_string(),
+                user.username.clone(),
+            ),
+        ])
+    }
+
+    /// Get the operator's current live config, reflecting the latest
+    /// successfully-validated reload (see [`config_reload`]) rather than
+    /// the snapshot passed to [`Operator::launch`].
     #[inline]
-    pub fn config(&self) -> &Config {
-        &self.0.config
+    pub fn config(&self) -> Arc<Config> {
+        self.0.config.load_full()
     }
 
     fn namespace(&self) -> &str {
-        &self.0.config.namespace
+        &self.0.namespace
     }
 
     /// Get a reference to the operator's config.
@@ -87,25 +220,105 @@ impl Operator {
         &self.0.metrics
     }
 
+    /// Snapshot-queryable state of the operator's background workers, eg.
+    /// for a "list workers" admin endpoint.
+    pub fn worker_registry(&self) -> WorkerRegistry {
+        self.0.workers.registry()
+    }
+
+    /// Whether the operator has finished initializing, isn't currently
+    /// draining for a graceful shutdown, and has a warm pod/service/volume
+    /// claim cache (see [`reconciler::Cache::synced`]) - so `/api/query`
+    /// never gets routed a request before its read-path checks can trust
+    /// the cache. Backs [`crate::server::run_admin_server`]'s `/ready` probe.
+    pub fn is_ready(&self) -> bool {
+        self.0.ready.load(std::sync::atomic::Ordering::SeqCst) && self.0.cache.synced()
+    }
+
+    /// Flip the readiness gate backing the admin server's `/ready` probe.
+    pub fn set_ready(&self, ready: bool) {
+        self.0.ready.store(ready, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Authenticate a `/api/query` caller against the configured
+    /// [`config::Config::auth`] backend. Uses the bearer token if the
+    /// caller sent one (OIDC), falling back to the SSH-key whitelist check
+    /// otherwise - see [`crate::auth`].
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        ssh_public_key: &str,
+        bearer_token: Option<&str>,
+    ) -> Result<config::User, AnyError> {
+        let credentials = match bearer_token {
+            Some(token) => crate::auth::Credentials::Bearer { token, ssh_public_key },
+            None => crate::auth::Credentials::SshKey { username, ssh_public_key },
+        };
+        self.0.auth_backend.verify(&credentials).await
+    }
+
+    /// [`Self::authenticate`] without a bearer token, ie. the original
+    /// username/SSH-public-key check.
+    pub async fn verify_user(&self, username: &str, ssh_public_key: &str) -> Result<config::User, AnyError> {
+        self.authenticate(username, ssh_public_key, None).await
+    }
+
     pub async fn launch(config: Config) -> Result<Self, AnyError> {
         tracing::info!("operator startup");
         let client = Client::connect().await?;
+        let auth_backend = crate::auth::build(&config, client.clone()).await?;
+        let (autoshutdown_tx, autoshutdown_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let namespace = config.namespace.clone();
+        let config: SharedConfig = Arc::new(arc_swap::ArcSwap::new(Arc::new(config)));
 
         let s = Operator(Arc::new(State {
-            config,
+            config: config.clone(),
+            namespace,
             client,
             metrics: OperatorMetrics::default(),
+            cache: Default::default(),
+            autoshutdown_queue: autoshutdown_tx,
+            workers: worker::WorkerManager::new(std::time::Duration::from_secs(30)),
+            auth_backend,
+            ready: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
         }));
         s.ensure_namespace().await?;
-
+        if s.config().auto_register_workspace_crd {
+            s.client()
+                .register_crd::<crate::workspace::Workspace>("kube-workspaces.foundational.cc")
+                .await
+                .context("Could not register Workspace CRD")?;
+        }
+        if let Err(err) = s.gc_orphaned_workspaces().await {
+            tracing::warn!(?err, "could not garbage collect orphaned workspaces on startup");
+        }
+        // Kubernetes client connected and namespace confirmed/created -
+        // safe to start reporting ready on the admin `/ready` probe.
+        s.set_ready(true);
+
+        // Reload the config on an interval, swapping it into `config` if
+        // the new value parses and validates cleanly, see
+        // `config_reload::ConfigReloadWorker`.
+        s.0.workers
+            .spawn(Box::new(config_reload::ConfigReloadWorker::new(config)));
         // Spawn the main check loop of the operator.
         tokio::task::spawn(s.clone().run_loop());
+        // Spawn the watchers that keep the status cache and workspace
+        // gauges reactively up to date, and that enqueue autoshutdown
+        // reconciles as pod events arrive.
+        reconciler::spawn(s.clone());
+        // Drain the autoshutdown reconcile queue - reacts promptly to pod
+        // changes instead of sweeping every workspace pod on a timer.
+        tokio::task::spawn(reconciler::run_autoshutdown_queue(s.clone(), autoshutdown_rx));
+        // Spawn the declarative `Workspace` CRD controller.
+        workspace_controller::spawn(s.clone());
         Ok(s)
     }
 
     /// Main loop of the operator that performs recurring checks.
     async fn run_loop(self) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+        let mut interval = tokio::time::interval(self.config().check_interval);
         loop {
             interval.tick().await;
             if let Err(err) = self.run_checks().await {
@@ -118,7 +331,10 @@ impl Operator {
         tracing::trace!("running check job");
         // TODO: mark operator as unhealthy if namespace could not be ensured.
         self.ensure_namespace().await?;
-        self.check_pods().await?;
+        // Autoshutdown is driven per pod by the reconcile queue, see
+        // `reconciler::run_autoshutdown_queue`, rather than a step here.
+        self.gc_orphaned_workspaces().await?;
+        self.check_image_updates().await?;
 
         if let Some(conf) = &self.config().prometheus_exporter {
             if conf.auto_register_operator_service_monitor {
@@ -197,100 +413,135 @@ impl Operator {
         Ok(())
     }
 
-    /// Check the currently running pods.
-    /// If auto shutdown is enabled, check status and shutdown down if approrpriate.
-    async fn check_pods(&self) -> Result<(), AnyError> {
-        let pod_label = Self::workspace_pod_label();
-
-        let pods = self
-            .client()
-            .pods_all(self.namespace(), Some(pod_label))
-            .await?;
-        let pod_metrics = self
-            .client()
-            .pod_metrics_list_all(self.namespace())
-            .await
-            .unwrap_or_else(|error| {
-                // The metrics API is optional and depends on a metrics-server
-                // deployment.
-                // Handle this gracefully by not propagating the error but just
-                // logging a warning.
-                // TODO: separate startup manual check for the pod metrics API
-                //  (for better error messages)
-                tracing::warn!(
-                    ?error,
-                    "could not obtain pod metrics - is the pod metrics API installed?"
-                );
-                Vec::new()
-            });
+    /// Check a single pod's autoshutdown state and act on it (shut it down,
+    /// schedule/cancel a pending shutdown, or leave it alone).
+    ///
+    /// Driven per pod by [`reconciler::run_autoshutdown_queue`] rather than
+    /// a fixed-interval sweep over every cached pod - pod watch events
+    /// enqueue a reconcile as soon as something changes, and the queue
+    /// requeues each pod afterwards so idle timers keep advancing even
+    /// between events.
+    async fn process_pod_autoshutdown(
+        &self,
+        pod: Pod,
+        metrics_opt: Option<PodMetrics>,
+    ) -> Result<(), AnyError> {
+        if !self.config().autoshutdown_enabled() {
+            return Ok(());
+        }
 
-        let mut available_count = 0;
-        let mut unavailable_count = 0;
+        let pod_name = client::pod_name(&pod);
+        let mut annotations = self.analyze_pod_autoshutdown(&pod, metrics_opt).await?;
+        let username = pod
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(Self::WORKSPACE_USER_LABEL));
 
-        for pod in pods {
-            let metrics = pod_metrics
-                .iter()
-                .find(|metrics| metrics.metadata.name == pod.metadata.name);
+        if let Some(username) = username {
+            self.record_idle_seconds(username, &annotations);
+        }
 
-            match WorkspacePhase::from_pod(&pod) {
-                WorkspacePhase::Starting => {
-                    unavailable_count += 1;
-                }
-                WorkspacePhase::Ready => {
-                    available_count += 1;
-                }
-                WorkspacePhase::Terminating
-                | WorkspacePhase::Unknown
-                | WorkspacePhase::NotFound => {}
+        match annotations.shutdown_decision(&self.config().auto_shutdown) {
+            ShutdownDecision::ShutdownNow => {
+                tracing::trace!(
+                    ?pod,
+                    ?annotations,
+                    "shutting down workspace pod due to auto shutdown"
+                );
+                self.client()
+                    .pod_delete(self.namespace(), client::pod_name(&pod))
+                    .await?;
+                self.metrics().auto_shutdown_count.inc();
+                tracing::info!(pod=%pod_name, "Workspace pod shut down due to autoshutdown");
             }
-
-            if self.config().autoshutdown_enabled() {
-                if let Err(err) = self.process_pod_autoshutdown(pod, metrics.cloned()).await {
-                    tracing::error!(error=?err, "Could not process pod autoshutdown");
+            ShutdownDecision::WarnThenShutdown => {
+                let newly_scheduled = annotations.shutdown_scheduled_at.is_none();
+                annotations.shutdown_scheduled_at.get_or_insert_with(chrono::Utc::now);
+                if newly_scheduled {
+                    self.notify_shutdown_scheduled(username.map(String::as_str), pod_name)
+                        .await;
                 }
+                let (patch, params) = annotations.to_patch();
+                self.client()
+                    .pod_patch(&self.config().namespace, pod_name, &patch, &params)
+                    .await?;
+            }
+            ShutdownDecision::Stay => {
+                // Activity resumed (or never stopped) - cancel any pending
+                // shutdown that was scheduled on a previous check.
+                annotations.shutdown_scheduled_at = None;
+                let (patch, params) = annotations.to_patch();
+                self.client()
+                    .pod_patch(&self.config().namespace, pod_name, &patch, &params)
+                    .await?;
             }
         }
 
-        self.0
-            .metrics
-            .workspace_available_count
-            .set(available_count);
-        self.0
-            .metrics
-            .workspace_unavailable_count
-            .set(unavailable_count);
-
         Ok(())
     }
 
-    async fn process_pod_autoshutdown(
-        &self,
-        pod: Pod,
-        metrics_opt: Option<PodMetrics>,
-    ) -> Result<(), AnyError> {
-        let pod_name = client::pod_name(&pod);
-        let annotations = self.analyze_pod_autoshutdown(&pod, metrics_opt).await?;
+    /// Notify that `username`'s workspace pod has entered the shutdown
+    /// warning phase, ie. it will be torn down after
+    /// [`config::AutoShutdown::shutdown_grace_period`] unless it becomes
+    /// active again. POSTs to [`config::AutoShutdown::warning_webhook`] if
+    /// configured, otherwise just logs - the scheduled timestamp is always
+    /// visible on the pod's [`PodMetricsAnnotion`] annotation regardless.
+    async fn notify_shutdown_scheduled(&self, username: Option<&str>, pod_name: &str) {
+        tracing::info!(user=?username, pod=%pod_name, "workspace idle, scheduling autoshutdown");
+
+        let webhook = match self.config().auto_shutdown.warning_webhook.as_ref() {
+            Some(webhook) => webhook,
+            None => return,
+        };
 
-        if annotations.should_shutdown(&self.config().auto_shutdown) {
-            tracing::trace!(
-                ?pod,
-                ?annotations,
-                "shutting down workspace pod due to auto shutdown"
-            );
-            self.client()
-                .pod_delete(self.namespace(), client::pod_name(&pod))
-                .await?;
-            tracing::info!(pod=%pod_name, "Workspace pod shut down due to autoshutdown");
-        } else {
-            // Update annotations.
-            tracing::trace!(?pod, ?annotations, "Updating pod autoshutdown annotations");
-            let (patch, params) = annotations.to_patch();
-            self.client()
-                .pod_patch(&self.config().namespace, pod_name, &patch, &params)
-                .await?;
+        let payload = serde_json::json!({
+            "username": username,
+            "pod": pod_name,
+            "grace_period_secs": self.config().auto_shutdown.shutdown_grace_period.as_secs(),
+        });
+        if let Err(error) = reqwest::Client::new().post(webhook).json(&payload).send().await {
+            tracing::warn!(?error, user=?username, pod=%pod_name, "could not deliver autoshutdown warning webhook");
         }
+    }
 
-        Ok(())
+    /// Publish the `cpu_idle_seconds`/`memory_idle_seconds`/`network_idle_seconds`
+    /// gauges for `username` from its freshly computed autoshutdown
+    /// annotations. Set to `0` while the corresponding signal isn't idle, so
+    /// the gauge always reflects the pod's current state rather than going
+    /// stale.
+    fn record_idle_seconds(&self, username: &str, annotations: &PodMetricsAnnotion) {
+        let now = chrono::Utc::now();
+        let label = UserLabel {
+            username: username.to_string(),
+        };
+
+        let cpu_seconds = annotations
+            .cpu_idle_since
+            .map(|since| now.signed_duration_since(since).num_seconds())
+            .unwrap_or(0);
+        self.metrics()
+            .cpu_idle_seconds
+            .get_or_create(&label)
+            .set(cpu_seconds);
+
+        let memory_seconds = annotations
+            .memory_idle_since
+            .map(|since| now.signed_duration_since(since).num_seconds())
+            .unwrap_or(0);
+        self.metrics()
+            .memory_idle_seconds
+            .get_or_create(&label)
+            .set(memory_seconds);
+
+        let network_seconds = annotations
+            .network_idle_since
+            .map(|since| now.signed_duration_since(since).num_seconds())
+            .unwrap_or(0);
+        self.metrics()
+            .network_idle_seconds
+            .get_or_create(&label)
+            .set(network_seconds);
     }
 
     /// Analyze auto-shutdown conditions for a pod.
@@ -299,66 +550,290 @@ impl Operator {
         pod: &Pod,
         metrics_opt: Option<PodMetrics>,
     ) -> Result<PodMetricsAnnotion, AnyError> {
-        let pod_name = pod
-            .metadata
-            .name
-            .as_ref()
-            .ok_or_else(|| anyhow!("Pod has no name"))?;
-
         let now = chrono::Utc::now();
 
         let mut annotations = PodMetricsAnnotion::from_pod(pod).unwrap_or_default();
 
         // If the last check was too long ago, we can't trust the metrics and need to start over.
         if let Some(last) = annotations.last_idle_check {
-            if now.signed_duration_since(last).to_std()? > std::time::Duration::from_secs(60 * 5) {
+            if now.signed_duration_since(last).to_std()? > self.config().auto_shutdown.metrics_staleness_threshold {
                 // Last check to old, so reset metrics.
                 annotations.cpu_idle_since = None;
+                annotations.memory_idle_since = None;
                 annotations.network_idle_since = None;
             }
         }
 
         let cfg = &self.config().auto_shutdown;
-        let cpu_is_idle = if let Some((cpu, metrics)) = cfg.cpu_usage.as_ref().zip(metrics_opt) {
-            client::pod_metrics_total_cpu(&metrics)? > cpu.cpu_threshold as i64
-        } else {
-            false
+        // `None` means "we don't know" (eg. metrics-server is unavailable) -
+        // in that case the existing `*_idle_since` bookkeeping is preserved
+        // as-is rather than advanced or reset, since we have no evidence
+        // either way.
+        let cpu_is_idle: Option<bool> = match (cfg.cpu_usage.as_ref(), metrics_opt.as_ref()) {
+            (Some(cpu), Some(metrics)) => {
+                Some(client::pod_metrics_total_cpu(metrics)? <= cpu.cpu_threshold as i64)
+            }
+            (Some(_), None) => None,
+            (None, _) => Some(false),
         };
 
-        let active_connections = self
-            .pod_active_tcp_connections(pod_name)
-            .await
-            .context("Could not determine active TCP connections of pod")?;
-        let network_is_idle = active_connections == 0;
+        let memory_is_idle: Option<bool> = match (cfg.memory_usage.as_ref(), metrics_opt.as_ref()) {
+            (Some(mem), Some(metrics)) => {
+                Some(client::pod_metrics_total_memory(metrics)? <= mem.memory_threshold as i64)
+            }
+            (Some(_), None) => None,
+            (None, _) => Some(false),
+        };
+
+        let (network_is_idle, network_bytes_total, network_bytes_sampled_at) = match cfg
+            .tcp_idle
+            .as_ref()
+        {
+            Some(net_cfg) => {
+                self.sample_network_idle(pod, net_cfg, now, &annotations)
+                    .await
+            }
+            None => (Some(false), None, None),
+        };
 
         let new_annotations = PodMetricsAnnotion {
             last_idle_check: Some(now),
-            cpu_idle_since: if cpu_is_idle {
-                annotations.cpu_idle_since.or(Some(now))
-            } else {
-                None
+            cpu_idle_since: match cpu_is_idle {
+                Some(true) => annotations.cpu_idle_since.or(Some(now)),
+                Some(false) => None,
+                None => annotations.cpu_idle_since,
             },
-            network_idle_since: if network_is_idle {
-                annotations.network_idle_since.or(Some(now))
-            } else {
-                None
+            memory_idle_since: match memory_is_idle {
+                Some(true) => annotations.memory_idle_since.or(Some(now)),
+                Some(false) => None,
+                None => annotations.memory_idle_since,
             },
+            network_idle_since: match network_is_idle {
+                Some(true) => annotations.network_idle_since.or(Some(now)),
+                Some(false) => None,
+                None => annotations.network_idle_since,
+            },
+            network_bytes_total,
+            network_bytes_sampled_at,
+            // Untouched by autoshutdown bookkeeping - carried over as-is so
+            // this write doesn't clobber what check_image_updates recorded.
+            last_checked_image_digest: annotations.last_checked_image_digest,
+            last_image_check: annotations.last_image_check,
+            // Left as-is here - process_pod_autoshutdown is the one place
+            // that sets/clears this, based on the ShutdownDecision computed
+            // from these freshly analyzed annotations.
+            shutdown_scheduled_at: annotations.shutdown_scheduled_at,
         };
         Ok(new_annotations)
     }
 
-    async fn pod_active_tcp_connections(&self, pod_name: &str) -> Result<usize, AnyError> {
-        let stdout = self
-            .client()
-            .pod_exec_stdout(
-                &self.config().namespace,
-                pod_name,
-                Self::POD_MAIN_CONTAINER_NAME,
-                vec!["ss", "--tcp", "--oneline", "--no-header"],
-            )
-            .await?;
+    /// Determine network idle-ness for a pod from cumulative kubelet
+    /// `/stats/summary` interface counters, rather than execing `ss` inside
+    /// the container (which requires `iproute2` in every workspace image
+    /// and treats any single open connection, even an idle keepalive, as
+    /// "active").
+    ///
+    /// Returns `(is_idle, new_byte_total, new_sampled_at)` - the latter two
+    /// are always returned so the caller can persist the running byte
+    /// counter regardless of whether a rate could be computed this round.
+    /// `is_idle` is `None` when the kubelet Summary data needed to judge
+    /// idle-ness isn't available, so the caller can leave the existing
+    /// `network_idle_since` bookkeeping untouched rather than resetting it.
+    async fn sample_network_idle(
+        &self,
+        pod: &Pod,
+        net_cfg: &config::TcpIdleAutoShutdown,
+        now: chrono::DateTime<chrono::Utc>,
+        previous: &PodMetricsAnnotion,
+    ) -> (Option<bool>, Option<u64>, Option<chrono::DateTime<chrono::Utc>>) {
+        let current_bytes = match self.pod_network_bytes(pod).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => {
+                // kubelet Summary data isn't available for this pod yet (or
+                // at all) - treat as unknown, same as the existing
+                // metrics-API handling: don't advance or reset idle timers,
+                // but keep the last known sample around.
+                return (None, previous.network_bytes_total, previous.network_bytes_sampled_at);
+            }
+            Err(error) => {
+                tracing::warn!(?error, "could not sample kubelet network stats for pod");
+                return (None, previous.network_bytes_total, previous.network_bytes_sampled_at);
+            }
+        };
+
+        let is_idle = match (previous.network_bytes_total, previous.network_bytes_sampled_at) {
+            (Some(prev_bytes), _) if current_bytes < prev_bytes => {
+                // Cumulative counters reset to zero on pod restart - skip
+                // this sample entirely and only establish a fresh baseline.
+                false
+            }
+            (Some(prev_bytes), Some(sampled_at)) => {
+                let elapsed = now
+                    .signed_duration_since(sampled_at)
+                    .to_std()
+                    .unwrap_or_default();
+                if elapsed > std::time::Duration::from_secs(60 * 5) || elapsed.is_zero() {
+                    // Either the first sample after a stale gap, or two
+                    // samples landed at the same instant - in both cases
+                    // only establish a baseline, don't declare idle yet.
+                    false
+                } else {
+                    let rate = (current_bytes - prev_bytes) as f64 / elapsed.as_secs_f64();
+                    rate <= net_cfg.byte_rate_threshold as f64
+                }
+            }
+            // No prior sample at all - this is the very first check.
+            _ => false,
+        };
+
+        (Some(is_idle), Some(current_bytes), Some(now))
+    }
+
+    /// Sum of cumulative rx+tx bytes reported for `pod` by its node's
+    /// kubelet `/stats/summary` endpoint. Returns `Ok(None)` if the pod
+    /// isn't scheduled yet, or the kubelet hasn't reported stats for it.
+    async fn pod_network_bytes(&self, pod: &Pod) -> Result<Option<u64>, AnyError> {
+        let node_name = match pod.spec.as_ref().and_then(|spec| spec.node_name.as_ref()) {
+            Some(node_name) => node_name,
+            None => return Ok(None),
+        };
+        let pod_name = match pod.metadata.name.as_ref() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let summary = self.client().node_stats_summary(node_name).await?;
+        let network = summary
+            .pod(self.namespace(), pod_name)
+            .and_then(|stats| stats.network.as_ref());
+        let network = match network {
+            Some(network) => network,
+            None => return Ok(None),
+        };
+
+        match (network.rx_bytes, network.tx_bytes) {
+            (Some(rx), Some(tx)) => Ok(Some(rx + tx)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Check whether any autoupdate-opted-in workspace pods are running a
+    /// stale image, recreating idle ones whose registry tag has moved to a
+    /// new digest.
+    ///
+    /// Disabled entirely unless [`config::Config::autoupdate`] is set and
+    /// enabled; even then, only pods carrying the
+    /// [`Self::IMAGE_AUTOUPDATE_ANNOTATION`] annotation are considered, so
+    /// autoupdate is opt-in per workspace rather than a blanket policy.
+    async fn check_image_updates(&self) -> Result<(), AnyError> {
+        let autoupdate = match self.config().autoupdate.as_ref() {
+            Some(cfg) if cfg.enable => cfg.clone(),
+            _ => return Ok(()),
+        };
+
+        for pod in self.0.cache.pods_snapshot() {
+            if let Err(err) = self.process_pod_autoupdate(&autoupdate, pod).await {
+                tracing::error!(error=?err, "could not check workspace pod for image updates");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process_pod_autoupdate(
+        &self,
+        autoupdate: &config::AutoUpdateConfig,
+        pod: Pod,
+    ) -> Result<(), AnyError> {
+        let opted_in = pod
+            .metadata
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(Self::IMAGE_AUTOUPDATE_ANNOTATION))
+            .map(|value| value == "registry")
+            .unwrap_or(false);
+        if !opted_in {
+            return Ok(());
+        }
+
+        // Recreating a stale pod only works safely for pods `workspace_controller`
+        // reconciles: it owns the pod via an owner reference and will recreate it
+        // on the next reconcile once it's gone. An ad hoc (non-CRD) workspace pod
+        // has nothing watching for its deletion, so deleting it here would just
+        // leave the user without a workspace until they start one again by hand.
+        let crd_owned = pod
+            .metadata
+            .owner_references
+            .as_ref()
+            .map(|refs| refs.iter().any(|r| r.kind == "Workspace"))
+            .unwrap_or(false);
+        if !crd_owned {
+            return Ok(());
+        }
+
+        let pod_name = client::pod_name(&pod).to_string();
+        let username = match pod
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(Self::WORKSPACE_USER_LABEL))
+        {
+            Some(username) => username.clone(),
+            None => return Ok(()),
+        };
+        let image = match pod
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.containers.iter().find(|c| c.name == Self::POD_MAIN_CONTAINER_NAME))
+            .and_then(|container| container.image.as_ref())
+        {
+            Some(image) => image.clone(),
+            None => return Ok(()),
+        };
+
+        let mut annotations = PodMetricsAnnotion::from_pod(&pod).unwrap_or_default();
+
+        let now = chrono::Utc::now();
+        if let Some(last) = annotations.last_image_check {
+            if now.signed_duration_since(last).to_std().unwrap_or_default() < autoupdate.check_interval {
+                // Checked recently enough, don't hit the registry again yet.
+                return Ok(());
+            }
+        }
+
+        let digest = registry::fetch_digest(&image)
+            .await
+            .with_context(|| format!("Could not resolve current registry digest for image '{}'", image))?;
 
-        Ok(stdout.trim().lines().count())
+        let previous_digest = annotations.last_checked_image_digest.replace(digest.clone());
+        annotations.last_image_check = Some(now);
+
+        match previous_digest {
+            // First observation establishes a baseline only - nothing to
+            // compare against yet.
+            None => {
+                tracing::trace!(user=%username, pod=%pod_name, %digest, "recorded baseline image digest for autoupdate tracking");
+            }
+            Some(previous) if previous != digest => {
+                // Never interrupt an active session - defer the recreation
+                // until the same idle/threshold rules autoshutdown uses say
+                // the workspace isn't in use.
+                if annotations.is_idle(&self.config().auto_shutdown) {
+                    tracing::info!(user=%username, pod=%pod_name, image=%image, previous_digest=%previous, new_digest=%digest, "new image digest detected, recreating idle workspace pod");
+                    self.client().pod_delete(self.namespace(), &pod_name).await?;
+                    return Ok(());
+                } else {
+                    tracing::debug!(user=%username, pod=%pod_name, "new image digest detected, deferring recreation until workspace is idle");
+                }
+            }
+            Some(_) => {}
+        }
+
+        let (patch, params) = annotations.to_patch();
+        self.client()
+            .pod_patch(&self.config().namespace, &pod_name, &patch, &params)
+            .await?;
+        Ok(())
     }
 
     /// ensure that the specified namespace exists.
@@ -393,6 +868,112 @@ impl Operator {
         Ok(())
     }
 
+    /// Delete the Pod/StatefulSet/Service/backup CronJob (and, if
+    /// [`config::Config::gc_reclaim_orphaned_volumes`] is set, the
+    /// home-directory PVC) of any workspace whose `WORKSPACE_USER_LABEL`
+    /// names a user the configured [`crate::auth::AuthBackend`] no longer
+    /// recognizes - sourced from there (rather than just
+    /// [`config::Config::users`]) so this doesn't wrongly sweep up
+    /// workspaces for users added via [`crate::user_provider::SqlUserProvider`]/
+    /// [`crate::user_provider::CrdUserProvider`]. A no-op if the backend has
+    /// no bounded user list to enumerate, see
+    /// [`crate::auth::AuthBackend::known_usernames`].
+    ///
+    /// Run once at startup and on every [`Self::run_checks`] sweep, so a
+    /// user removed from config doesn't leave a Pod/Service/PVC behind
+    /// forever.
+    async fn gc_orphaned_workspaces(&self) -> Result<(), AnyError> {
+        let configured: std::collections::HashSet<String> =
+            match self.0.auth_backend.known_usernames().await? {
+                Some(usernames) => usernames,
+                None => {
+                    tracing::debug!(
+                        "auth backend has no enumerable user list, skipping orphaned-workspace gc"
+                    );
+                    return Ok(());
+                }
+            };
+
+        let mut orphaned = std::collections::HashSet::new();
+        for pod in self.client().pods_all(self.namespace(), None).await? {
+            if let Some(username) = pod
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(Self::WORKSPACE_USER_LABEL))
+            {
+                if !configured.contains(username.as_str()) {
+                    orphaned.insert(username.clone());
+                }
+            }
+        }
+        for claim in self
+            .client()
+            .volume_claims_all(self.namespace(), None)
+            .await?
+        {
+            if let Some(username) = claim
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(Self::WORKSPACE_USER_LABEL))
+            {
+                if !configured.contains(username.as_str()) {
+                    orphaned.insert(username.clone());
+                }
+            }
+        }
+
+        for username in orphaned {
+            tracing::info!(user=%username, "garbage collecting orphaned workspace for removed user");
+            // The removed user no longer has a `config::User` entry (that's
+            // the whole point), but all the resource-naming helpers only
+            // need the username, so a throwaway one works fine here.
+            let user = config::User {
+                username: username.clone(),
+                ssh_public_key: String::new(),
+                home_volume_size: None,
+            };
+
+            self.client()
+                .pod_delete(self.namespace(), &Self::user_pod_name(&user))
+                .await
+                .or_else(Self::ignore_not_found)?;
+            self.client()
+                .statefulset_delete(self.namespace(), &Self::user_statefulset_name(&user))
+                .await
+                .or_else(Self::ignore_not_found)?;
+            self.client()
+                .service_delete(self.namespace(), &Self::user_service_name(&user))
+                .await
+                .or_else(Self::ignore_not_found)?;
+            self.client()
+                .cronjob_delete(self.namespace(), &Self::user_backup_cronjob_name(&user))
+                .await
+                .or_else(Self::ignore_not_found)?;
+
+            if self.config().gc_reclaim_orphaned_volumes {
+                self.client()
+                    .volume_claim_delete(self.namespace(), &Self::user_home_volume_name(&user))
+                    .await
+                    .or_else(Self::ignore_not_found)?;
+            } else {
+                tracing::debug!(user=%username, "retaining orphaned home volume (gc_reclaim_orphaned_volumes is disabled)");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Treat a `404 Not Found` from a delete call as success, since the
+    /// desired end state - the object being gone - is already achieved.
+    fn ignore_not_found(err: kube::Error) -> Result<(), AnyError> {
+        match err {
+            kube::Error::Api(ref e) if e.code == 404 => Ok(()),
+            err => Err(err.into()),
+        }
+    }
+
     fn user_home_volume_name(user: &config::User) -> String {
         format!("workspace-{}", user.username)
     }
@@ -401,21 +982,91 @@ impl Operator {
         &self,
         user: &config::User,
     ) -> Result<PersistentVolumeClaim, AnyError> {
-        let claim_name = Self::user_home_volume_name(user);
-
-        // First, check if a pod is already running.
-        let claim_opt = self
-            .client()
-            .volume_claim_opt(&self.config().namespace, &claim_name)
-            .await?;
+        // Served from the watch-driven cache (warm once `is_ready()` is
+        // true) rather than a live `GET`, see `reconciler::Cache::volume_claim`.
+        let claim_opt = self.0.cache.volume_claim(&user.username);
 
         if let Some(claim) = claim_opt {
-            Ok(claim)
+            self.maybe_resize_home_volume(user, claim).await
         } else {
             self.create_user_home_volume(user).await
         }
     }
 
+    /// Grow an existing home volume's storage request if
+    /// [`config::Config::home_volume_size_for`] now resolves to more than
+    /// what's live, eg. because an admin raised `max_home_volume_size`. This
+    /// triggers a CSI volume expansion; Kubernetes rejects shrinking a PVC
+    /// outright, so this never attempts that.
+    ///
+    /// Only patches claims whose storage class advertises
+    /// `allow_volume_expansion` - otherwise the resize would just fail
+    /// server-side, so this logs a warning and leaves the claim alone.
+    async fn maybe_resize_home_volume(
+        &self,
+        user: &config::User,
+        claim: PersistentVolumeClaim,
+    ) -> Result<PersistentVolumeClaim, AnyError> {
+        let claim_name = Self::user_home_volume_name(user);
+
+        let live_storage = claim
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.resources.as_ref())
+            .and_then(|res| res.requests.as_ref())
+            .and_then(|requests| requests.get("storage"));
+        let live_parsed = match live_storage {
+            Some(quantity) => kube_quantity::ParsedQuantity::try_from(quantity.clone())
+                .context("Could not parse live home volume storage request")?,
+            // No storage request recorded on the claim - nothing sensible to compare against.
+            None => return Ok(claim),
+        };
+
+        let configured_size = self.config().home_volume_size_for(user)?;
+        let configured_parsed = config::Config::parse_storage_quantity("max_home_volume_size", &configured_size)?;
+
+        if configured_parsed <= live_parsed {
+            return Ok(claim);
+        }
+
+        let storage_class_name = claim.spec.as_ref().and_then(|spec| spec.storage_class_name.as_ref());
+        let expandable = match storage_class_name {
+            Some(name) => self
+                .client()
+                .storage_class_opt(name)
+                .await?
+                .and_then(|sc| sc.allow_volume_expansion)
+                .unwrap_or(false),
+            None => false,
+        };
+        if !expandable {
+            tracing::warn!(
+                user = %user.username,
+                volume = %claim_name,
+                storage_class = ?storage_class_name,
+                "home volume needs to grow but its storage class does not allow volume expansion, skipping"
+            );
+            return Ok(claim);
+        }
+
+        tracing::info!(
+            user = %user.username,
+            volume = %claim_name,
+            to = %configured_size,
+            "expanding home volume"
+        );
+        self.client()
+            .volume_claim_patch(
+                self.namespace(),
+                &claim_name,
+                &Patch::Merge(serde_json::json!({
+                    "spec": { "resources": { "requests": { "storage": configured_size } } }
+                })),
+            )
+            .await
+            .context("Could not patch home volume to expand it")
+    }
+
     pub async fn create_user_home_volume(
         &self,
         user: &config::User,
@@ -427,6 +1078,7 @@ impl Operator {
             metadata: ObjectMeta {
                 name: Some(claim_name.clone()),
                 namespace: Some(ns.to_string()),
+                labels: Some(Self::workspace_pod_labels(user)),
                 ..Default::default()
             },
             spec: Some(PersistentVolumeClaimSpec {
@@ -436,7 +1088,7 @@ impl Operator {
                     requests: Some(
                         vec![(
                             "storage".to_string(),
-                            Quantity(self.config().max_home_volume_size.clone()),
+                            Quantity(self.config().home_volume_size_for(user)?),
                         )]
                         .into_iter()
                         .collect(),
@@ -454,6 +1106,82 @@ impl Operator {
             .context("Could not create persistent volume for user home directory")
     }
 
+    fn user_ssh_secret_name(user: &config::User) -> String {
+        format!("workspace-{}-ssh", user.username)
+    }
+
+    /// Create or update the `Secret` holding the user's `authorized_keys`
+    /// file, mounted read-only into the workspace pod by
+    /// [`Self::build_user_pod_spec`].
+    ///
+    /// Using server-side apply here means rotating a user's key is just a
+    /// Secret update - the key never needs to be baked into the pod spec or
+    /// command, and never appears in `kubectl describe pod`/command history.
+    pub async fn ensure_user_ssh_secret(&self, user: &config::User) -> Result<Secret, AnyError> {
+        let name = Self::user_ssh_secret_name(user);
+
+        let schema = Secret {
+            metadata: ObjectMeta {
+                name: Some(name),
+                namespace: Some(self.namespace().to_string()),
+                labels: Some(Self::workspace_pod_labels(user)),
+                ..Default::default()
+            },
+            type_: Some("Opaque".to_string()),
+            data: Some(BTreeMap::from_iter(vec![(
+                "authorized_keys".to_string(),
+                ByteString(user.ssh_public_key.clone().into_bytes()),
+            )])),
+            ..Default::default()
+        };
+
+        self.client()
+            .apply(Some(self.namespace()), &schema, "kube-workspaces.foundational.cc")
+            .await
+            .context("Could not create or update SSH authorized_keys secret for user")
+    }
+
+    /// Stamp a Kubernetes garbage-collector owner reference onto an
+    /// already-materialized child object (PVC/Service/Secret/Pod/...).
+    ///
+    /// Used by [`workspace_controller`] so that deleting a `Workspace`
+    /// automatically cleans up everything it owns, instead of the
+    /// controller having to track and delete each child by hand.
+    pub(super) async fn set_owner_reference<K>(
+        &self,
+        mut obj: K,
+        owner: &OwnerReference,
+    ) -> Result<K, AnyError>
+    where
+        K: Resource<DynamicType = ()> + Clone + std::fmt::Debug,
+        K: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let already_owned = obj
+            .meta()
+            .owner_references
+            .as_ref()
+            .map(|refs| refs.iter().any(|r| r.uid == owner.uid))
+            .unwrap_or(false);
+        if already_owned {
+            return Ok(obj);
+        }
+
+        let namespace = obj.meta().namespace.clone();
+        obj.meta_mut()
+            .owner_references
+            .get_or_insert_with(Vec::new)
+            .push(owner.clone());
+
+        self.client()
+            .apply(
+                namespace.as_deref(),
+                &obj,
+                "kube-workspaces.foundational.cc/workspace-controller",
+            )
+            .await
+            .context("Could not set owner reference on workspace child object")
+    }
+
     fn user_service_name(user: &config::User) -> String {
         format!("workspace-{}", user.username)
     }
@@ -521,24 +1249,248 @@ impl Operator {
             .context("Could not create service for user")
     }
 
-    fn user_pod_name(user: &config::User) -> String {
-        format!("workspace-{}", user.username)
+    fn user_headless_service_name(user: &config::User) -> String {
+        format!("{}-headless", Self::user_service_name(user))
     }
 
-    // pub async fn get_user_pod(&self, user: &config::User) -> Result<Pod, AnyError> {
-    //     let pod_name = Self::user_pod_name(&user);
-    //     self.client
-    //         .pod(&self.config.namespace, &pod_name)
-    //         .await
-    //         .map_err(Into::into)
-    // }
+    /// Ensure the headless (`clusterIP: None`) `Service` a workspace
+    /// `StatefulSet`'s `serviceName` must reference exists, creating it if
+    /// necessary.
+    ///
+    /// This is separate from [`Self::ensure_user_service`]'s NodePort
+    /// Service - that one is what external SSH clients connect through,
+    /// while this one only exists to give the StatefulSet's pod stable
+    /// DNS, as Kubernetes requires.
+    async fn ensure_user_headless_service(&self, user: &config::User) -> Result<Service, AnyError> {
+        let name = Self::user_headless_service_name(user);
+        if let Some(existing) = self.client().service_opt(self.namespace(), &name).await? {
+            return Ok(existing);
+        }
+
+        let svc = Service {
+            metadata: ObjectMeta {
+                name: Some(name),
+                namespace: Some(self.namespace().to_string()),
+                ..Default::default()
+            },
+            spec: Some(ServiceSpec {
+                cluster_ip: Some("None".to_string()),
+                selector: Some(Self::workspace_pod_labels(user)),
+                ports: Some(vec![ServicePort {
+                    name: Some("ssh".to_string()),
+                    port: 22,
+                    target_port: Some(IntOrString::String("ssh".into())),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
 
-    pub async fn get_user_pod_opt(&self, user: &config::User) -> Result<Option<Pod>, AnyError> {
-        let pod_name = Self::user_pod_name(user);
         self.client()
-            .pod_opt(self.namespace(), &pod_name)
+            .service_create(self.namespace(), &svc)
             .await
-            .map_err(Into::into)
+            .context("Could not create headless service for user StatefulSet")
+    }
+
+    fn user_pod_name(user: &config::User) -> String {
+        format!("workspace-{}", user.username)
+    }
+
+    // pub async fn get_user_pod(&self, user: &config::User) -> Result<Pod, AnyError> {
+    //     let pod_name = Self::user_pod_name(&user);
+    //     self.client
+    //         .pod(&self.config.namespace, &pod_name)
+    //         .await
+    //         .map_err(Into::into)
+    // }
+
+    /// Find the user's managed workspace pod, located via the
+    /// `workspace-user` label selector rather than a fixed name.
+    pub async fn get_user_pod_opt(&self, user: &config::User) -> Result<Option<Pod>, AnyError> {
+        let label = (
+            Self::WORKSPACE_USER_LABEL.to_string(),
+            user.username.clone(),
+        );
+        let pods = self.client().pods_all(self.namespace(), Some(label)).await?;
+        Ok(pods.into_iter().next())
+    }
+
+    /// Shell commands that prepare the mounted home volume for `user`:
+    /// create `.ssh` and fix up ownership/permissions. `authorized_keys`
+    /// itself is mounted read-only from the secret managed by
+    /// [`Self::ensure_user_ssh_secret`] with `defaultMode: 0600`, so it
+    /// doesn't need handling here.
+    ///
+    /// Shared between the inline (main-container) and init-container
+    /// provisioning paths - see [`config::SshProvisioning::use_init_container`].
+    fn user_home_setup_commands(user: &config::User) -> Vec<String> {
+        vec![
+            format!("mkdir -p /home/{}/.ssh", user.username),
+            format!("chown {u}:{u} /home/{u}", u = user.username),
+            format!("chown {u}:{u} /home/{u}/.ssh", u = user.username),
+            format!("chmod 755 /home/{}", user.username),
+            format!("chmod 755 /home/{}/.ssh", user.username),
+        ]
+    }
+
+    /// Build the command that provisions the SSH user and starts `sshd`
+    /// inside the workspace container, per [`config::SshProvisioning`].
+    ///
+    /// With the built-in modes the user's public key itself is not baked in
+    /// here - it is mounted read-only at `authorized_keys` from the secret
+    /// managed by [`Self::ensure_user_ssh_secret`], so it never appears in
+    /// the pod spec/command and can be rotated without recreating the pod.
+    /// A [`config::SshProvisioningMode::Custom`] command that uses the
+    /// `{ssh_public_key}` placeholder does embed the key in the command.
+    fn user_pod_command(user: &config::User, provisioning: &config::SshProvisioning) -> Vec<String> {
+        if let config::SshProvisioningMode::Custom { command } = &provisioning.mode {
+            let rendered = command
+                .replace("{username}", &user.username)
+                .replace("{ssh_public_key}", &user.ssh_public_key);
+            return vec!["bash".to_string(), "-c".to_string(), rendered];
+        }
+
+        let mut steps = Vec::new();
+        if matches!(provisioning.mode, config::SshProvisioningMode::AptGet) {
+            steps.push("apt-get update".to_string());
+            steps.push("apt-get install -y openssh-server".to_string());
+        }
+        steps.push(format!(
+            "adduser --gecos \"\" --no-create-home --disabled-password {}",
+            user.username
+        ));
+        if !provisioning.use_init_container {
+            steps.extend(Self::user_home_setup_commands(user));
+        }
+        // // Must create run dir for sshd.
+        // "/usr/sbin/sshd -d",
+        // "mkdir -p /run/sshd",
+        steps.push("service ssh start".to_string());
+        steps.push("sleep infinity".to_string());
+
+        vec!["bash".to_string(), "-c".to_string(), steps.join(" && ")]
+    }
+
+    /// Build the init container that prepares a workspace pod's home-volume
+    /// permissions ahead of the main `sshd` container starting, when
+    /// [`config::SshProvisioning::use_init_container`] is enabled.
+    ///
+    /// `None` when disabled, or when using
+    /// [`config::SshProvisioningMode::Custom`], which is responsible for
+    /// its own setup.
+    fn build_user_pod_init_container(
+        user: &config::User,
+        provisioning: &config::SshProvisioning,
+    ) -> Option<Container> {
+        if !provisioning.use_init_container || matches!(provisioning.mode, config::SshProvisioningMode::Custom { .. }) {
+            return None;
+        }
+
+        Some(Container {
+            name: "home-setup".to_string(),
+            image: Some("busybox".to_string()),
+            command: Some(vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                Self::user_home_setup_commands(user).join(" && "),
+            ]),
+            volume_mounts: Some(vec![VolumeMount {
+                mount_path: format!("/home/{}", user.username),
+                name: "home".to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+    }
+
+    /// Build the main workspace container and pod-level settings.
+    ///
+    /// The caller is responsible for wiring the home-directory volume (an
+    /// explicit `PersistentVolumeClaimVolumeSource`, see
+    /// [`Self::create_user_pod`]).
+    ///
+    /// `readiness_probe_initial_delay`/`readiness_probe_period` configure the
+    /// SSH-port readiness probe, see [`config::ConfigSourcePodReadinessProbe`].
+    ///
+    /// `provisioning` selects how SSH access gets set up on cold start, see
+    /// [`config::SshProvisioning`].
+    fn build_user_pod_spec(
+        user: &config::User,
+        spec_template: &PodSpec,
+        readiness_probe_initial_delay: std::time::Duration,
+        readiness_probe_period: std::time::Duration,
+        provisioning: &config::SshProvisioning,
+    ) -> PodSpec {
+        let mut spec = spec_template.clone();
+        let main_container = if let Some(container) = spec.containers.get_mut(0) {
+            container
+        } else {
+            spec.containers.push(Container {
+                ..Default::default()
+            });
+            spec.containers.get_mut(0).unwrap()
+        };
+
+        main_container.image.get_or_insert("ubuntu".into());
+        main_container.name = Self::POD_MAIN_CONTAINER_NAME.to_string();
+        main_container.command = Some(Self::user_pod_command(user, provisioning));
+
+        if let Some(init_container) = Self::build_user_pod_init_container(user, provisioning) {
+            spec.init_containers.get_or_insert(Vec::new()).push(init_container);
+        }
+
+        main_container
+            .volume_mounts
+            .get_or_insert(Vec::new())
+            .push(VolumeMount {
+                mount_path: format!("/home/{}", user.username),
+                name: "home".to_string(),
+                ..Default::default()
+            });
+
+        main_container
+            .volume_mounts
+            .get_or_insert(Vec::new())
+            .push(VolumeMount {
+                mount_path: format!("/home/{}/.ssh/authorized_keys", user.username),
+                name: "ssh-keys".to_string(),
+                sub_path: Some("authorized_keys".to_string()),
+                read_only: Some(true),
+                ..Default::default()
+            });
+
+        spec.volumes.get_or_insert(Vec::new()).push(Volume {
+            name: "ssh-keys".to_string(),
+            secret: Some(SecretVolumeSource {
+                secret_name: Some(Self::user_ssh_secret_name(user)),
+                default_mode: Some(0o600),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        main_container
+            .ports
+            .get_or_insert(Vec::new())
+            .push(ContainerPort {
+                container_port: 22,
+                name: Some("ssh".into()),
+                ..Default::default()
+            });
+
+        main_container.readiness_probe = Some(Probe {
+            tcp_socket: Some(TCPSocketAction {
+                host: None,
+                port: IntOrString::String("ssh".into()),
+            }),
+            initial_delay_seconds: Some(readiness_probe_initial_delay.as_secs() as i32),
+            period_seconds: Some(readiness_probe_period.as_secs() as i32),
+            timeout_seconds: Some(3),
+            ..Default::default()
+        });
+
+        spec
     }
 
     #[tracing::instrument]
@@ -553,126 +1505,132 @@ impl Operator {
         tracing::debug!(user=%user.username, pod_name=%pod_name, "Creating user pod");
 
         let home_volume = self.ensure_user_home_volume(user).await?;
+        self.ensure_user_ssh_secret(user).await?;
+
+        let mut spec = Self::build_user_pod_spec(
+            user,
+            spec_template,
+            self.config().pod_readiness_probe.initial_delay,
+            self.config().pod_readiness_probe.period,
+            &self.config().ssh_provisioning,
+        );
+        spec.volumes.get_or_insert(Vec::new()).push(Volume {
+            name: "home".to_string(),
+            persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                claim_name: home_volume.metadata.name.as_ref().unwrap().clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
 
-        // Create the pod.
-
-        let command = vec![
-            "bash".to_string(),
-            "-c".to_string(),
-            vec![
-                "apt-get update",
-                "apt-get install -y openssh-server",
-                &format!(
-                    "adduser --gecos \"\" --no-create-home --disabled-password {}",
-                    user.username
-                ),
-                &format!("mkdir -p /home/{}/.ssh", user.username),
-                &format!(
-                    "echo '{}' > /home/{}/.ssh/authorized_keys",
-                    user.ssh_public_key, user.username
-                ),
-                // Ensure correct permissions.
-                &format!("chown {u}:{u} /home/{u}", u = user.username),
-                &format!("chown {u}:{u} /home/{u}/.ssh", u = user.username),
-                &format!("chmod 755 /home/{}", user.username),
-                &format!("chmod 755 /home/{}/.ssh", user.username),
-                &format!("chmod 644 /home/{}/.ssh/authorized_keys", user.username),
-                // // Must create run dir for sshd.
-                // "/usr/sbin/sshd -d",
-                // "mkdir -p /run/sshd",
-                "service ssh start",
-                "sleep infinity",
-            ]
-            .join(" && "),
-        ];
-
-        let spec = {
-            let mut spec = spec_template.clone();
-            let main_container = if let Some(container) = spec.containers.get_mut(0) {
-                container
-            } else {
-                spec.containers.push(Container {
-                    ..Default::default()
-                });
-                spec.containers.get_mut(0).unwrap()
-            };
+        let schema = Pod {
+            metadata: ObjectMeta {
+                name: Some(pod_name),
+                namespace: Some(ns.to_string()),
+                labels: Some(Self::workspace_pod_labels(user)),
+                ..Default::default()
+            },
+            spec: Some(spec),
+            status: None,
+        };
 
-            main_container.image.get_or_insert("ubuntu".into());
-            main_container.name = Self::POD_MAIN_CONTAINER_NAME.to_string();
-            main_container.command = Some(command);
+        let pod = tokio::time::timeout(
+            self.config().timeouts.pod_create,
+            self.client().pod_create(ns, &schema),
+        )
+        .await
+        .context("Timed out creating pod for user")?
+        .context("Could not create pod for user")?;
+        tracing::info!(user=%user.username, "user_pod_created");
+        Ok(pod)
+    }
 
-            main_container
-                .volume_mounts
-                .get_or_insert(Vec::new())
-                .push(VolumeMount {
-                    mount_path: format!("/home/{}", user.username),
-                    name: "home".to_string(),
-                    ..Default::default()
-                });
-
-            main_container
-                .ports
-                .get_or_insert(Vec::new())
-                .push(ContainerPort {
-                    container_port: 22,
-                    name: Some("ssh".into()),
-                    ..Default::default()
-                });
+    fn user_statefulset_name(user: &config::User) -> String {
+        format!("workspace-{}", user.username)
+    }
 
-            main_container.readiness_probe = Some(Probe {
-                tcp_socket: Some(TCPSocketAction {
-                    host: None,
-                    port: IntOrString::String("ssh".into()),
-                }),
-                initial_delay_seconds: Some(60),
-                period_seconds: Some(30),
-                timeout_seconds: Some(3),
-                ..Default::default()
-            });
+    #[tracing::instrument]
+    async fn create_user_statefulset(
+        &self,
+        user: &config::User,
+        spec_template: &PodSpec,
+    ) -> Result<StatefulSet, AnyError> {
+        let ns = self.namespace();
+        let sts_name = Self::user_statefulset_name(user);
 
-            spec.volumes.get_or_insert(Vec::new()).push(Volume {
-                name: "home".to_string(),
-                persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
-                    claim_name: home_volume.metadata.name.as_ref().unwrap().clone(),
+        tracing::debug!(user=%user.username, statefulset=%sts_name, "Creating user StatefulSet");
+
+        self.ensure_user_ssh_secret(user).await?;
+        self.ensure_user_headless_service(user).await?;
+
+        let spec = Self::build_user_pod_spec(
+            user,
+            spec_template,
+            self.config().pod_readiness_probe.initial_delay,
+            self.config().pod_readiness_probe.period,
+            &self.config().ssh_provisioning,
+        );
+
+        let volume_claim_template = PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some("home".to_string()),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                storage_class_name: self.config().storage_class.clone(),
+                access_modes: Some(vec!["ReadWriteOnce".to_string()]),
+                resources: Some(ResourceRequirements {
+                    requests: Some(
+                        vec![(
+                            "storage".to_string(),
+                            Quantity(self.config().home_volume_size_for(user)?),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
                     ..Default::default()
                 }),
                 ..Default::default()
-            });
-
-            spec
+            }),
+            ..Default::default()
         };
 
-        let schema = Pod {
+        let schema = StatefulSet {
             metadata: ObjectMeta {
-                name: Some(pod_name),
+                name: Some(sts_name.clone()),
                 namespace: Some(ns.to_string()),
-                labels: Some(
-                    vec![
-                        (
-                            Self::WORKSPACE_POD_LABEL.to_string(),
-                            Self::WORKSPACE_POD_LABEL_VALUE.to_string(),
-                        ),
-                        (
-                            Self::WORKSPACE_USER_LABEL.to_string(),
-                            user.username.clone(),
-                        ),
-                    ]
-                    .into_iter()
-                    .collect(),
-                ),
+                labels: Some(Self::workspace_pod_labels(user)),
                 ..Default::default()
             },
-            spec: Some(spec),
+            spec: Some(StatefulSetSpec {
+                replicas: Some(1),
+                service_name: Self::user_headless_service_name(user),
+                selector: LabelSelector {
+                    match_labels: Some(Self::workspace_pod_labels(user)),
+                    match_expressions: None,
+                },
+                template: PodTemplateSpec {
+                    metadata: Some(ObjectMeta {
+                        labels: Some(Self::workspace_pod_labels(user)),
+                        ..Default::default()
+                    }),
+                    spec: Some(spec),
+                },
+                volume_claim_templates: Some(vec![volume_claim_template]),
+                ..Default::default()
+            }),
             status: None,
         };
 
-        let pod = self
-            .client()
-            .pod_create(ns, &schema)
-            .await
-            .context("Could not create pod for user")?;
-        tracing::info!(user=%user.username, "user_pod_created");
-        Ok(pod)
+        let sts = tokio::time::timeout(
+            self.config().timeouts.pod_create,
+            self.client().statefulset_create(ns, &schema),
+        )
+        .await
+        .context("Timed out creating StatefulSet for user")?
+        .context("Could not create StatefulSet for user")?;
+        tracing::info!(user=%user.username, statefulset=%sts_name, "user_statefulset_created");
+        Ok(sts)
     }
 
     pub async fn ensure_user_pod(
@@ -690,7 +1648,9 @@ impl Operator {
         let pod = if let Some(pod) = self.get_user_pod_opt(user).await? {
             pod
         } else {
-            self.create_user_pod(user, spec).await?
+            let pod = self.create_user_pod(user, spec).await?;
+            self.metrics().pod_start_count.inc();
+            pod
         };
 
         let node_name_opt = pod.spec.as_ref().and_then(|x| x.node_name.as_ref());
@@ -702,15 +1662,248 @@ impl Operator {
 
         tracing::info!(user=%user.username, pod=%pod_name, "Pod for user ensured");
 
+        let metrics = self.pod_metrics_opt(&pod_name).await;
+
         Ok(WorkspaceStatus {
             phase: WorkspacePhase::from_pod(&pod),
             pod: Some(pod),
             service: Some(service),
             node,
+            metrics,
         })
     }
 
+    /// Block until `user`'s workspace pod is [`WorkspacePhase::Ready`]
+    /// (Running, with all containers reporting ready), or `timeout` elapses.
+    ///
+    /// Unlike [`Self::ensure_user_pod`], which returns as soon as the pod
+    /// object exists even while it's still `Pending`, this lets a caller
+    /// opt into waiting until the workspace is actually usable (eg. SSH is
+    /// reachable) before returning.
+    pub async fn wait_until_ready(
+        &self,
+        user: &config::User,
+        timeout: std::time::Duration,
+    ) -> Result<Pod, WaitUntilReadyError> {
+        let pod_name = Self::user_pod_name(user);
+        let api = self.client().api::<Pod>(Some(self.namespace()));
+
+        let watch_result = tokio::time::timeout(
+            timeout,
+            await_condition(api, &pod_name, |pod: Option<&Pod>| {
+                pod.map(WorkspacePhase::from_pod)
+                    .map(|phase| phase == WorkspacePhase::Ready)
+                    .unwrap_or(false)
+            }),
+        )
+        .await
+        .map_err(|_| WaitUntilReadyError::Timeout(timeout))?;
+
+        watch_result
+            .context("Error watching workspace pod readiness")?
+            .ok_or_else(|| {
+                anyhow!(
+                    "Workspace pod '{}' was deleted while waiting for it to become ready",
+                    pod_name
+                )
+            })
+            .map_err(WaitUntilReadyError::from)
+    }
+
+    /// Like [`Self::ensure_user_pod`], but blocks until the pod reaches
+    /// [`WorkspacePhase::Ready`] (using [`Self::wait_until_ready`], bounded
+    /// by [`config::Config::pod_ready_timeout`]) instead of returning as
+    /// soon as the pod object exists.
+    pub async fn ensure_user_pod_ready(
+        &self,
+        user: &config::User,
+        spec: &PodSpec,
+    ) -> Result<WorkspaceStatus, WaitUntilReadyError> {
+        let start = std::time::Instant::now();
+        let mut status = self.ensure_user_pod(user, spec).await?;
+        let pod = self
+            .wait_until_ready(user, self.config().pod_ready_timeout)
+            .await?;
+        self.metrics()
+            .pod_ready_latency
+            .observe(start.elapsed().as_secs_f64());
+        status.phase = WorkspacePhase::from_pod(&pod);
+        status.pod = Some(pod);
+        Ok(status)
+    }
+
+    /// Like [`Self::ensure_user_pod`], but provisions a single-replica
+    /// `StatefulSet` instead of a bare `Pod`.
+    ///
+    /// The home directory is requested through the `StatefulSet`'s
+    /// `volumeClaimTemplates` rather than a separately managed
+    /// `PersistentVolumeClaim`, and the pod, once scheduled, is located via
+    /// the `workspace-user` label selector instead of a fixed pod name -
+    /// this gives the workspace resilience against both pod crashes and
+    /// node failures, since the `StatefulSet` controller recreates it (and
+    /// reattaches the same volume) wherever it is rescheduled.
+    pub async fn ensure_user_statefulset(
+        &self,
+        user: &config::User,
+        spec: &PodSpec,
+    ) -> Result<WorkspaceStatus, AnyError> {
+        tracing::debug!(user=%user.username, "Ensuring StatefulSet for user");
+        let service = self.ensure_user_service(user).await?;
+
+        let sts_name = Self::user_statefulset_name(user);
+        match self
+            .client()
+            .statefulset_opt(self.namespace(), &sts_name)
+            .await?
+        {
+            None => {
+                self.create_user_statefulset(user, spec).await?;
+            }
+            // Scaled to zero by `user_statefulset_shutdown` - scale it back up.
+            Some(sts) if sts.spec.as_ref().and_then(|s| s.replicas) == Some(0) => {
+                self.client()
+                    .statefulset_patch(
+                        self.namespace(),
+                        &sts_name,
+                        &Patch::Merge(serde_json::json!({ "spec": { "replicas": 1 } })),
+                    )
+                    .await
+                    .context("Could not scale up user StatefulSet")?;
+            }
+            Some(_) => {}
+        }
+
+        let pod = self.get_user_pod_opt(user).await?;
+
+        let node = match pod
+            .as_ref()
+            .and_then(|p| p.spec.as_ref())
+            .and_then(|s| s.node_name.as_ref())
+        {
+            Some(name) => Some(self.client().node(name).await?),
+            None => None,
+        };
+
+        let phase = pod
+            .as_ref()
+            .map(WorkspacePhase::from_pod)
+            .unwrap_or(WorkspacePhase::Starting);
+
+        let metrics = match pod.as_ref().and_then(|p| p.metadata.name.as_deref()) {
+            Some(pod_name) => self.pod_metrics_opt(pod_name).await,
+            None => None,
+        };
+
+        tracing::info!(user=%user.username, statefulset=%sts_name, "StatefulSet for user ensured");
+
+        Ok(WorkspaceStatus {
+            phase,
+            pod,
+            service: Some(service),
+            node,
+            metrics,
+        })
+    }
+
+    /// Block until `user`'s `StatefulSet`-backed workspace pod is
+    /// [`WorkspacePhase::Ready`], or `timeout` elapses.
+    ///
+    /// Unlike [`Self::wait_until_ready`] (which watches a fixed pod name via
+    /// `kube_runtime::wait::await_condition`), a `StatefulSet`'s pod name
+    /// carries the `-<ordinal>` suffix rather than the bare workspace name,
+    /// so it has to be located by the `workspace-user` label instead - see
+    /// [`Self::get_user_pod_opt`]. There's no watch-based equivalent for an
+    /// arbitrary label selector in `kube_runtime::wait`, so this polls.
+    async fn wait_until_ready_by_label(
+        &self,
+        user: &config::User,
+        timeout: std::time::Duration,
+    ) -> Result<Pod, WaitUntilReadyError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(pod) = self
+                .get_user_pod_opt(user)
+                .await
+                .map_err(WaitUntilReadyError::Other)?
+            {
+                if WorkspacePhase::from_pod(&pod) == WorkspacePhase::Ready {
+                    return Ok(pod);
+                }
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(WaitUntilReadyError::Timeout(timeout));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Like [`Self::ensure_user_statefulset`], but blocks until the pod
+    /// reaches [`WorkspacePhase::Ready`] (bounded by
+    /// [`config::Config::pod_ready_timeout`]) instead of returning as soon
+    /// as the `StatefulSet` object exists.
+    pub async fn ensure_user_statefulset_ready(
+        &self,
+        user: &config::User,
+        spec: &PodSpec,
+    ) -> Result<WorkspaceStatus, WaitUntilReadyError> {
+        let start = std::time::Instant::now();
+        let mut status = self
+            .ensure_user_statefulset(user, spec)
+            .await
+            .map_err(WaitUntilReadyError::Other)?;
+        let pod = self
+            .wait_until_ready_by_label(user, self.config().pod_ready_timeout)
+            .await?;
+        self.metrics()
+            .pod_ready_latency
+            .observe(start.elapsed().as_secs_f64());
+        status.phase = WorkspacePhase::from_pod(&pod);
+        status.pod = Some(pod);
+        Ok(status)
+    }
+
+    /// Provision `user`'s workspace compute per
+    /// [`config::Config::workspace_backend`] - a bare `Pod` (the default,
+    /// [`Self::ensure_user_pod`]) or a single-replica `StatefulSet`
+    /// ([`Self::ensure_user_statefulset`]).
+    pub async fn ensure_user_workspace(
+        &self,
+        user: &config::User,
+        spec: &PodSpec,
+    ) -> Result<WorkspaceStatus, AnyError> {
+        match self.config().workspace_backend {
+            config::WorkspaceBackend::Pod => self.ensure_user_pod(user, spec).await,
+            config::WorkspaceBackend::StatefulSet => self.ensure_user_statefulset(user, spec).await,
+        }
+    }
+
+    /// Like [`Self::ensure_user_workspace`], but waits for readiness - see
+    /// [`Self::ensure_user_pod_ready`]/[`Self::ensure_user_statefulset_ready`].
+    pub async fn ensure_user_workspace_ready(
+        &self,
+        user: &config::User,
+        spec: &PodSpec,
+    ) -> Result<WorkspaceStatus, WaitUntilReadyError> {
+        match self.config().workspace_backend {
+            config::WorkspaceBackend::Pod => self.ensure_user_pod_ready(user, spec).await,
+            config::WorkspaceBackend::StatefulSet => {
+                self.ensure_user_statefulset_ready(user, spec).await
+            }
+        }
+    }
+
     pub async fn workspace_status(&self, user: &config::User) -> Result<WorkspaceStatus, AnyError> {
+        if let Some(mut status) = self.0.cache.status(&user.username) {
+            // Usage metrics are intentionally not part of the watch-driven
+            // cache (see `reconciler::Cache::status`), so they are fetched
+            // live on top of the cached pod/service/node state.
+            status.metrics = self.pod_metrics_opt(&Self::user_pod_name(user)).await;
+            return Ok(status);
+        }
+
+        // Fall back to a live lookup if the reconciler has not observed
+        // anything for this user yet (eg. right after operator startup,
+        // before the initial watch list completes).
         let service = self.get_user_service_opt(user).await?;
         let pod = self.get_user_pod_opt(user).await?;
 
@@ -722,11 +1915,13 @@ impl Operator {
                     } else {
                         None
                     };
+                let metrics = self.pod_metrics_opt(Self::user_pod_name(user).as_str()).await;
                 Ok(WorkspaceStatus {
                     service: Some(service),
                     node,
                     phase: WorkspacePhase::from_pod(&pod),
                     pod: Some(pod),
+                    metrics,
                 })
             }
             _ => Ok(WorkspaceStatus {
@@ -734,10 +1929,242 @@ impl Operator {
                 service,
                 pod: None,
                 node: None,
+                metrics: None,
             }),
         }
     }
 
+    /// How often [`Self::watch_workspace_status`] re-checks the status
+    /// cache for changes.
+    const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    /// Stream `user`'s [`WorkspaceStatus`] as it changes, for the
+    /// `/api/watch` SSE endpoint.
+    ///
+    /// The cache behind [`Self::workspace_status`] is already kept current
+    /// by the watch-driven [`reconciler`], so rather than standing up a
+    /// second Kubernetes watch on the same pod, this just polls that cache
+    /// at [`Self::WATCH_POLL_INTERVAL`] and yields a new item only when the
+    /// externally-visible [`WorkspacePhase`] or SSH address actually
+    /// changes. The stream ends once the workspace reaches
+    /// [`WorkspacePhase::Ready`] or a terminal phase
+    /// ([`WorkspacePhase::Terminating`]/[`WorkspacePhase::NotFound`]) -
+    /// callers only care about the transition out of `Starting`.
+    pub fn watch_workspace_status(
+        self,
+        user: config::User,
+    ) -> impl futures::Stream<Item = Result<WorkspaceStatus, AnyError>> {
+        futures::stream::unfold(
+            (self, user, None::<(WorkspacePhase, Option<String>, Option<i32>)>),
+            |(op, user, mut last)| async move {
+                loop {
+                    let status = match op.workspace_status(&user).await {
+                        Ok(status) => status,
+                        Err(error) => return Some((Err(error), (op, user, last))),
+                    };
+
+                    let key = (status.phase.clone(), status.public_address(), status.ssh_port());
+                    let changed = last.as_ref() != Some(&key);
+                    let done = matches!(
+                        status.phase,
+                        WorkspacePhase::Ready | WorkspacePhase::Terminating | WorkspacePhase::NotFound
+                    );
+
+                    if changed {
+                        last = Some(key);
+                        return Some((Ok(status), (op, user, last)));
+                    }
+                    if done {
+                        return None;
+                    }
+
+                    tokio::time::sleep(Self::WATCH_POLL_INTERVAL).await;
+                }
+            },
+        )
+    }
+
+    /// Fetch live metrics for a pod, degrading gracefully to `None` if the
+    /// metrics-server API is not available or has not scraped the pod yet.
+    async fn pod_metrics_opt(&self, pod_name: &str) -> Option<client::PodMetrics> {
+        self.client()
+            .pod_metrics_opt(self.namespace(), pod_name)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::debug!(
+                    ?error,
+                    pod = pod_name,
+                    "could not obtain pod metrics - is the pod metrics API installed?"
+                );
+                None
+            })
+    }
+
+    /// Stream the user's workspace pod logs.
+    /// Set `follow` to keep the stream open as new log lines are produced.
+    pub async fn user_pod_log_stream(
+        &self,
+        user: &config::User,
+        follow: bool,
+        tail_lines: Option<i64>,
+        since_seconds: Option<i64>,
+    ) -> Result<impl tokio::io::AsyncBufRead + Send, AnyError> {
+        let pod_name = Self::user_pod_name(user);
+        let params = kube::api::LogParams {
+            container: Some(Self::POD_MAIN_CONTAINER_NAME.to_string()),
+            follow,
+            tail_lines,
+            since_seconds,
+            ..Default::default()
+        };
+
+        self.client()
+            .pod_log_stream(self.namespace(), &pod_name, &params)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Run a command inside the user's workspace pod over the Kubernetes
+    /// exec WebSocket, without requiring the pod's SSH NodePort to be
+    /// reachable.
+    pub async fn exec_user_pod(
+        &self,
+        user: &config::User,
+        command: Vec<&str>,
+        container: Option<&str>,
+        tty: bool,
+    ) -> Result<client::PodExecOutput, AnyError> {
+        let pod_name = Self::user_pod_name(user);
+        let container = container.unwrap_or(Self::POD_MAIN_CONTAINER_NAME);
+        self.client()
+            .pod_exec(self.namespace(), &pod_name, container, command, tty)
+            .await
+    }
+
+    /// Copy a tar archive into the user's workspace pod by piping it to
+    /// `tar xf -` over the exec WebSocket, extracting at `dest`.
+    ///
+    /// `dest` must stay within the user's home directory - this is
+    /// enforced by [`validate_path_in_home`] to prevent a malicious path
+    /// from writing outside the workspace (eg. `../../etc`).
+    pub async fn copy_into_user_pod(
+        &self,
+        user: &config::User,
+        dest: &str,
+        archive: Vec<u8>,
+    ) -> Result<client::PodExecIoOutput, AnyError> {
+        let dest = validate_path_in_home(user, dest)?;
+        let pod_name = Self::user_pod_name(user);
+        self.client()
+            .pod_exec_io(
+                self.namespace(),
+                &pod_name,
+                Self::POD_MAIN_CONTAINER_NAME,
+                vec!["tar", "xf", "-", "-C", &dest],
+                Some(archive),
+            )
+            .await
+    }
+
+    /// Copy `src` out of the user's workspace pod as a tar archive, by
+    /// running `tar cf -` over the exec WebSocket.
+    ///
+    /// `src` must stay within the user's home directory, enforced by
+    /// [`validate_path_in_home`].
+    pub async fn copy_from_user_pod(
+        &self,
+        user: &config::User,
+        src: &str,
+    ) -> Result<client::PodExecIoOutput, AnyError> {
+        let src = validate_path_in_home(user, src)?;
+        let pod_name = Self::user_pod_name(user);
+        self.client()
+            .pod_exec_io(
+                self.namespace(),
+                &pod_name,
+                Self::POD_MAIN_CONTAINER_NAME,
+                vec!["tar", "cf", "-", "-C", "/", src.trim_start_matches('/')],
+                None,
+            )
+            .await
+    }
+
+    /// Run a command inside the user's workspace pod over the Kubernetes
+    /// exec WebSocket, pumping `stdin`/`stdout`/`stderr` through the
+    /// process' attached streams instead of buffering the exchange.
+    ///
+    /// This is what lets a gateway proxy a terminal session straight
+    /// through the cluster API server, without the user's pod needing a
+    /// reachable SSH NodePort.
+    pub async fn exec_in_pod<R, W1, W2>(
+        &self,
+        user: &config::User,
+        argv: Vec<&str>,
+        mut stdin: R,
+        mut stdout: W1,
+        mut stderr: W2,
+    ) -> Result<Option<i32>, AnyError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+        W1: tokio::io::AsyncWrite + Unpin,
+        W2: tokio::io::AsyncWrite + Unpin,
+    {
+        let pod_name = Self::user_pod_name(user);
+        let mut session = tokio::time::timeout(
+            self.config().timeouts.pod_exec,
+            self.client().pod_exec_stream(
+                self.namespace(),
+                &pod_name,
+                Self::POD_MAIN_CONTAINER_NAME,
+                argv,
+            ),
+        )
+        .await
+        .context("Timed out starting exec session in pod")??;
+
+        let mut proc_stdin = session.stdin().ok_or_else(|| anyhow!("stdin not attached"))?;
+        let mut proc_stdout = session
+            .stdout()
+            .ok_or_else(|| anyhow!("stdout not attached"))?;
+        let mut proc_stderr = session
+            .stderr()
+            .ok_or_else(|| anyhow!("stderr not attached"))?;
+
+        let copy_stdin = async { tokio::io::copy(&mut stdin, &mut proc_stdin).await };
+        let copy_stdout = async { tokio::io::copy(&mut proc_stdout, &mut stdout).await };
+        let copy_stderr = async { tokio::io::copy(&mut proc_stderr, &mut stderr).await };
+
+        // Errors here typically mean one side of the pipe closed (eg. the
+        // caller dropped their stdin), which just ends that copy loop - the
+        // command's actual success/failure is reported via the exit code.
+        let _ = tokio::join!(copy_stdin, copy_stdout, copy_stderr);
+
+        Ok(session.wait().await)
+    }
+
+    /// Open an interactive shell session in the user's workspace pod over
+    /// the Kubernetes exec WebSocket, for a remote-terminal gateway.
+    ///
+    /// Unlike [`Self::exec_in_pod`], this doesn't pump the session's bytes
+    /// anywhere itself - it hands back the raw `AsyncRead`/`AsyncWrite`
+    /// halves so the HTTP layer can proxy them onto a browser or CLI
+    /// terminal over its own websocket, without requiring the pod's SSH
+    /// NodePort to be reachable.
+    pub async fn pod_exec_interactive(&self, user: &config::User) -> Result<client::PodExecSession, AnyError> {
+        let pod_name = Self::user_pod_name(user);
+        tokio::time::timeout(
+            self.config().timeouts.pod_exec,
+            self.client().pod_exec_interactive(
+                self.namespace(),
+                &pod_name,
+                Self::POD_MAIN_CONTAINER_NAME,
+                vec!["bash", "-l"],
+            ),
+        )
+        .await
+        .context("Timed out starting interactive exec session in pod")?
+    }
+
     pub async fn user_pod_shutdown(&self, user: &config::User) -> Result<(), AnyError> {
         let name = Self::user_pod_name(user);
         tracing::debug!(pod=%name, user=%user.username, "deleting user pod");
@@ -746,9 +2173,235 @@ impl Operator {
         self.client()
             .service_delete(self.namespace(), &Self::user_service_name(user))
             .await?;
+
+        // The backup CronJob is tied to the workspace's lifecycle: once the
+        // pod/home-volume are gone there's nothing left worth backing up.
+        if self.config().backup.is_some() {
+            self.client()
+                .cronjob_delete(self.namespace(), &Self::user_backup_cronjob_name(user))
+                .await?;
+        }
+
+        self.metrics().pod_shutdown_count.inc();
         tracing::info!(user=%user.username, pod=%name, "user pod deleted");
         Ok(())
     }
+
+    /// Gracefully shut down a `StatefulSet`-backed workspace by scaling it
+    /// to zero replicas, rather than deleting it outright.
+    ///
+    /// Unlike [`Self::user_pod_shutdown`] (which deletes the bare `Pod`,
+    /// losing its `StatefulSet`-managed identity entirely), this keeps the
+    /// `StatefulSet` - and its `volumeClaimTemplates`-owned home volume -
+    /// around so that [`Self::ensure_user_statefulset`] can scale it back
+    /// up to restart the workspace later.
+    pub async fn user_statefulset_shutdown(&self, user: &config::User) -> Result<(), AnyError> {
+        let name = Self::user_statefulset_name(user);
+        tracing::debug!(statefulset=%name, user=%user.username, "scaling down user statefulset");
+        self.client()
+            .statefulset_patch(
+                self.namespace(),
+                &name,
+                &Patch::Merge(serde_json::json!({ "spec": { "replicas": 0 } })),
+            )
+            .await
+            .context("Could not scale down user StatefulSet")?;
+        self.metrics().pod_shutdown_count.inc();
+        tracing::info!(user=%user.username, statefulset=%name, "user statefulset scaled to zero");
+        Ok(())
+    }
+
+    /// Shut down `user`'s workspace compute per
+    /// [`config::Config::workspace_backend`] - see
+    /// [`Self::user_pod_shutdown`]/[`Self::user_statefulset_shutdown`].
+    pub async fn user_workspace_shutdown(&self, user: &config::User) -> Result<(), AnyError> {
+        match self.config().workspace_backend {
+            config::WorkspaceBackend::Pod => self.user_pod_shutdown(user).await,
+            config::WorkspaceBackend::StatefulSet => self.user_statefulset_shutdown(user).await,
+        }
+    }
+
+    fn user_backup_cronjob_name(user: &config::User) -> String {
+        format!("workspace-{}-backup", user.username)
+    }
+
+    /// Ensure a per-user `CronJob` exists that periodically tars up the
+    /// user's home volume onto the configured backup target volume.
+    ///
+    /// Returns `Ok(None)` without touching the cluster if
+    /// [`config::Config::backup`] is unset/disabled - backups are opt-in.
+    pub async fn ensure_user_backup_cronjob(
+        &self,
+        user: &config::User,
+    ) -> Result<Option<CronJob>, AnyError> {
+        let backup = match self.config().backup.as_ref() {
+            Some(backup) if backup.enable => backup,
+            _ => return Ok(None),
+        };
+
+        let name = Self::user_backup_cronjob_name(user);
+        if let Some(existing) = self.client().cronjob_opt(self.namespace(), &name).await? {
+            return Ok(Some(existing));
+        }
+
+        let home_volume_name = "home";
+        let target_volume_name = "backup-target";
+        let backup_dir = format!("/backup/{}", user.username);
+
+        let container = Container {
+            name: "backup".to_string(),
+            image: Some(backup.image.clone()),
+            command: Some(vec!["bash".to_string(), "-c".to_string()]),
+            args: Some(vec![format!(
+                "mkdir -p {dir} && tar -cf {dir}/home-$(date +%Y%m%d%H%M%S).tar -C /home/{user} .",
+                dir = backup_dir,
+                user = user.username,
+            )]),
+            volume_mounts: Some(vec![
+                VolumeMount {
+                    name: home_volume_name.to_string(),
+                    mount_path: format!("/home/{}", user.username),
+                    read_only: Some(true),
+                    ..Default::default()
+                },
+                VolumeMount {
+                    name: target_volume_name.to_string(),
+                    mount_path: "/backup".to_string(),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let pod_spec = PodSpec {
+            containers: vec![container],
+            restart_policy: Some("OnFailure".to_string()),
+            volumes: Some(vec![
+                Volume {
+                    name: home_volume_name.to_string(),
+                    persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                        claim_name: Self::user_home_volume_name(user),
+                        read_only: Some(true),
+                    }),
+                    ..Default::default()
+                },
+                Volume {
+                    name: target_volume_name.to_string(),
+                    persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                        claim_name: backup.target_volume_claim.clone(),
+                        read_only: Some(false),
+                    }),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let schema = CronJob {
+            metadata: ObjectMeta {
+                name: Some(name.clone()),
+                namespace: Some(self.namespace().to_string()),
+                labels: Some(Self::workspace_pod_labels(user)),
+                ..Default::default()
+            },
+            spec: Some(CronJobSpec {
+                schedule: backup.schedule.clone(),
+                successful_jobs_history_limit: Some(backup.retention),
+                job_template: JobTemplateSpec {
+                    metadata: None,
+                    spec: Some(JobSpec {
+                        template: PodTemplateSpec {
+                            metadata: Some(ObjectMeta {
+                                labels: Some(Self::workspace_pod_labels(user)),
+                                ..Default::default()
+                            }),
+                            spec: Some(pod_spec),
+                        },
+                        ..Default::default()
+                    }),
+                },
+                ..Default::default()
+            }),
+            status: None,
+        };
+
+        let cronjob = self
+            .client()
+            .cronjob_create(self.namespace(), &schema)
+            .await
+            .context("Could not create backup CronJob for user")?;
+        tracing::info!(user=%user.username, cronjob=%name, "user backup cronjob created");
+        Ok(Some(cronjob))
+    }
+}
+
+/// Error from [`Operator::wait_until_ready`]. A dedicated `Timeout` variant
+/// lets a caller distinguish "still not ready" from any other failure and
+/// decide whether eg. to retry, surface a specific message to the user, or
+/// fall back to returning the not-yet-ready status.
+#[derive(Debug)]
+pub enum WaitUntilReadyError {
+    Timeout(std::time::Duration),
+    Other(AnyError),
+}
+
+impl std::fmt::Display for WaitUntilReadyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(
+                f,
+                "timed out after {:?} waiting for workspace pod to become ready",
+                timeout
+            ),
+            Self::Other(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl std::error::Error for WaitUntilReadyError {}
+
+impl From<AnyError> for WaitUntilReadyError {
+    fn from(err: AnyError) -> Self {
+        Self::Other(err)
+    }
+}
+
+/// Resolve `path` against the user's home directory and check that it stays
+/// inside it, rejecting `..` segments that would escape it (eg.
+/// `../../etc/passwd`). Relative paths are resolved relative to the home
+/// directory; absolute paths must already be under it.
+///
+/// Returns the normalized absolute path on success.
+fn validate_path_in_home(user: &config::User, path: &str) -> Result<String, AnyError> {
+    let home = format!("/home/{}", user.username);
+    let full = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{}/{}", home, path)
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for part in full.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(anyhow!("Path '{}' escapes the workspace home directory", path));
+                }
+            }
+            other => components.push(other),
+        }
+    }
+    let normalized = format!("/{}", components.join("/"));
+
+    if normalized == home || normalized.starts_with(&format!("{}/", home)) {
+        Ok(normalized)
+    } else {
+        Err(anyhow!(
+            "Path '{}' is outside the workspace home directory",
+            path
+        ))
+    }
 }
 
 /// Custom annotation data applied to pods.
@@ -757,7 +2410,48 @@ impl Operator {
 struct PodMetricsAnnotion {
     last_idle_check: Option<chrono::DateTime<chrono::Utc>>,
     cpu_idle_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the pod's memory usage first dropped to/below
+    /// `config::MemoryIdleAutoShutdown::memory_threshold`. Tracked
+    /// separately from [`Self::cpu_idle_since`] so a pod that is CPU-idle
+    /// but pinned by leaked memory can still be caught.
+    #[serde(default)]
+    memory_idle_since: Option<chrono::DateTime<chrono::Utc>>,
     network_idle_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Cumulative rx+tx byte counter last sampled from the kubelet
+    /// `/stats/summary` endpoint, used to compute a byte-rate between
+    /// checks. See [`Operator::sample_network_idle`].
+    #[serde(default)]
+    network_bytes_total: Option<u64>,
+    /// When [`Self::network_bytes_total`] was sampled.
+    #[serde(default)]
+    network_bytes_sampled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Digest observed at the last [`Operator::check_image_updates`] check,
+    /// used to detect when the workspace's image tag moves upstream.
+    #[serde(default)]
+    last_checked_image_digest: Option<String>,
+    /// When [`Self::last_checked_image_digest`] was last refreshed, so
+    /// checks can be throttled to `autoupdate.check_interval`.
+    #[serde(default)]
+    last_image_check: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the pod first satisfied [`Self::is_idle`], ie. when its shutdown
+    /// warning grace period started. `None` while the pod is active, and
+    /// reset back to `None` if activity resumes before the grace period
+    /// elapses. See [`Self::shutdown_decision`].
+    #[serde(default)]
+    shutdown_scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Outcome of [`PodMetricsAnnotion::shutdown_decision`].
+enum ShutdownDecision {
+    /// The pod is active (or not idle long enough yet) - no action needed.
+    Stay,
+    /// The pod is idle and a shutdown is scheduled, but its grace period
+    /// hasn't elapsed yet - keep it scheduled (warning the user when it is
+    /// first scheduled), don't delete the pod.
+    WarnThenShutdown,
+    /// The pod is still idle and its grace period has elapsed - tear it
+    /// down now.
+    ShutdownNow,
 }
 
 impl PodMetricsAnnotion {
@@ -794,39 +2488,89 @@ impl PodMetricsAnnotion {
         )
     }
 
-    /// Compare idle times against the shutdown config and determine if the
-    /// pod should be shut down.
-    fn should_shutdown(&self, config: &config::AutoShutdown) -> bool {
-        let now = chrono::Utc::now();
+    /// For a single configured signal, whether it has been idle long enough
+    /// to count towards [`Self::is_idle`]. Only called for configured
+    /// signals - one that isn't configured at all doesn't participate in the
+    /// [`config::IdleMatchPolicy`] evaluation.
+    fn signal_idle(
+        minimum_idle_time: std::time::Duration,
+        since: Option<&chrono::DateTime<chrono::Utc>>,
+    ) -> bool {
+        match since {
+            Some(since) => {
+                chrono::Utc::now()
+                    .signed_duration_since(*since)
+                    .to_std()
+                    .unwrap_or_default()
+                    > minimum_idle_time
+            }
+            // Signal is configured but the pod isn't currently idle on it.
+            None => false,
+        }
+    }
 
-        let netcfg = config.tcp_idle.as_ref();
-        let net_idle = self.network_idle_since.as_ref();
+    /// Compare idle times against the shutdown config and determine if the
+    /// pod is currently eligible for autoshutdown.
+    ///
+    /// Each configured signal (network/CPU/memory) contributes whether *it*
+    /// has been idle for longer than its own `minimum_idle_time`; the results
+    /// are then combined according to `config.match_policy` - `All` requires
+    /// every configured signal to agree (the long-standing default), `Any`
+    /// shuts down as soon as one of them does.
+    fn is_idle(&self, config: &config::AutoShutdown) -> bool {
+        let signals: Vec<bool> = [
+            config
+                .tcp_idle
+                .as_ref()
+                .map(|cfg| Self::signal_idle(cfg.minimum_idle_time, self.network_idle_since.as_ref())),
+            config
+                .cpu_usage
+                .as_ref()
+                .map(|cfg| Self::signal_idle(cfg.minimum_idle_time, self.cpu_idle_since.as_ref())),
+            config
+                .memory_usage
+                .as_ref()
+                .map(|cfg| Self::signal_idle(cfg.minimum_idle_time, self.memory_idle_since.as_ref())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if signals.is_empty() {
+            return false;
+        }
 
-        let mut should_shutdown = false;
+        match config.match_policy {
+            config::IdleMatchPolicy::All => signals.iter().all(|&idle| idle),
+            config::IdleMatchPolicy::Any => signals.iter().any(|&idle| idle),
+        }
+    }
 
-        if let Some((cfg, since)) = netcfg.zip(net_idle) {
-            let exceeded =
-                now.signed_duration_since(*since).to_std().unwrap() > cfg.minimum_idle_time;
-            if !exceeded {
-                return false;
-            } else {
-                should_shutdown = true;
-            }
+    /// Decide what, if anything, to do about this pod's autoshutdown state.
+    ///
+    /// Shutdown is two-phase: once [`Self::is_idle`] first returns `true`,
+    /// [`Self::shutdown_scheduled_at`] is set and [`ShutdownDecision::WarnThenShutdown`]
+    /// is returned until `config.shutdown_grace_period` elapses, giving the
+    /// user a window to resume activity (which resets `shutdown_scheduled_at`
+    /// back to `None`) before the pod is actually deleted.
+    fn shutdown_decision(&self, config: &config::AutoShutdown) -> ShutdownDecision {
+        if !self.is_idle(config) {
+            return ShutdownDecision::Stay;
         }
 
-        let cpucfg = config.cpu_usage.as_ref();
-        let cpu_idle = self.cpu_idle_since.as_ref();
+        let scheduled_at = match self.shutdown_scheduled_at {
+            Some(scheduled_at) => scheduled_at,
+            None => return ShutdownDecision::WarnThenShutdown,
+        };
 
-        if let Some((cfg, since)) = cpucfg.zip(cpu_idle) {
-            let exceeded =
-                now.signed_duration_since(*since).to_std().unwrap() > cfg.minimum_idle_time;
-            if !exceeded {
-                return false;
-            } else {
-                should_shutdown = true;
-            }
+        let elapsed = chrono::Utc::now()
+            .signed_duration_since(scheduled_at)
+            .to_std()
+            .unwrap_or_default();
+        if elapsed >= config.shutdown_grace_period {
+            ShutdownDecision::ShutdownNow
+        } else {
+            ShutdownDecision::WarnThenShutdown
         }
-
-        should_shutdown
     }
 }
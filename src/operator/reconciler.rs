@@ -0,0 +1,383 @@
+//! Watch-driven reconciler that keeps an in-memory [`WorkspaceStatus`] cache
+//! up to date, so `workspace_status` can answer `PodStatus` requests without
+//! issuing a live Kubernetes API call on every request.
+//!
+//! Each of the pod/service/PVC/node watchers runs its own event loop and
+//! applies `Applied`/`Deleted`/`Restarted` events to shared state behind a
+//! `Mutex`, resyncing its slice of the cache whenever the underlying watch
+//! restarts to avoid drift. [`Cache::synced`] only reports true once the
+//! pod, service and PVC watches have delivered their initial lists (the
+//! node watch is not waited on), which [`Operator::is_ready`] gates on - so
+//! `/api/query` never sees traffic routed to it before the cache is
+//! actually warm.
+//!
+//! [`watch_pods`] also drives autoshutdown reconciliation: rather than
+//! sweeping every cached pod on a fixed interval, it enqueues a
+//! per-pod [`Operator::process_pod_autoshutdown`] reconcile whenever that
+//! pod's watch event fires, and [`run_autoshutdown_queue`] requeues each pod
+//! after [`AUTOSHUTDOWN_REQUEUE_INTERVAL`] so idle timers keep advancing
+//! even while a pod sees no Kubernetes events of its own.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::{Node, PersistentVolumeClaim, Pod, Service};
+use kube_runtime::watcher::Event;
+use tokio::sync::mpsc;
+
+use crate::config::Username;
+
+use super::{
+    types::{WorkspacePhase, WorkspaceStatus},
+    Operator,
+};
+
+/// Sender half of the autoshutdown reconcile queue - pod names to
+/// (re-)check, enqueued by [`watch_pods`] and by [`run_autoshutdown_queue`]
+/// itself for its requeue-after behavior.
+pub(super) type ReconcileQueue = mpsc::UnboundedSender<String>;
+
+/// How long after reconciling a pod it gets requeued, so autoshutdown idle
+/// timers keep advancing between Kubernetes events rather than only on
+/// pod changes.
+const AUTOSHUTDOWN_REQUEUE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub(super) struct Cache {
+    pods: Mutex<HashMap<Username, Pod>>,
+    services: Mutex<HashMap<Username, Service>>,
+    nodes: Mutex<HashMap<String, Node>>,
+    /// Workspace home-directory PVCs, keyed by username. See
+    /// [`Cache::volume_claim`].
+    volume_claims: Mutex<HashMap<Username, PersistentVolumeClaim>>,
+    /// Flipped to `true` once the corresponding watch has delivered its
+    /// initial list (a `Restarted` event), so [`Cache::synced`] can gate
+    /// the operator's readiness probe on the cache actually being warm -
+    /// see [`Operator::is_ready`].
+    pods_synced: AtomicBool,
+    services_synced: AtomicBool,
+    volume_claims_synced: AtomicBool,
+}
+
+pub(super) type SharedCache = Arc<Cache>;
+
+impl Cache {
+    /// Cached home-directory PVC for `username`, if the watcher has seen
+    /// one. Used by read-path existence checks (eg.
+    /// [`Operator::ensure_user_home_volume`]) instead of a live `GET` -
+    /// safe once [`Self::synced`] is true, which the admin readiness probe
+    /// already gates on.
+    pub(super) fn volume_claim(&self, username: &str) -> Option<PersistentVolumeClaim> {
+        self.volume_claims.lock().unwrap().get(username).cloned()
+    }
+
+    /// Whether every watch this cache depends on has delivered its initial
+    /// list, ie. whether it's safe for read-path queries to trust it.
+    pub(super) fn synced(&self) -> bool {
+        self.pods_synced.load(Ordering::SeqCst)
+            && self.services_synced.load(Ordering::SeqCst)
+            && self.volume_claims_synced.load(Ordering::SeqCst)
+    }
+
+    /// Build the cached [`WorkspaceStatus`] for a user from the last
+    /// observed pod/service/node, if the reconciler has seen anything for
+    /// them yet.
+    pub(super) fn status(&self, username: &str) -> Option<WorkspaceStatus> {
+        let pod = self.pods.lock().unwrap().get(username).cloned();
+        let service = self.services.lock().unwrap().get(username).cloned();
+        if pod.is_none() && service.is_none() {
+            return None;
+        }
+
+        let node = pod
+            .as_ref()
+            .and_then(|p| p.spec.as_ref())
+            .and_then(|s| s.node_name.as_ref())
+            .and_then(|name| self.nodes.lock().unwrap().get(name).cloned());
+
+        let phase = pod
+            .as_ref()
+            .map(WorkspacePhase::from_pod)
+            .unwrap_or(WorkspacePhase::NotFound);
+
+        Some(WorkspaceStatus {
+            phase,
+            pod,
+            service,
+            node,
+            // Usage metrics change far more often than pod/service/node
+            // state and are cheap to fetch on demand, so they are
+            // deliberately left out of the watch-driven cache.
+            metrics: None,
+        })
+    }
+
+    /// Snapshot of all currently cached workspace pods, used by
+    /// [`Operator::check_image_updates`] so it doesn't need its own `LIST`
+    /// call against the API server - the watcher above already keeps this
+    /// up to date.
+    pub(super) fn pods_snapshot(&self) -> Vec<Pod> {
+        self.pods.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Recompute the per-phase workspace gauges from the currently cached
+    /// pods.
+    fn update_metrics(&self, op: &Operator) {
+        let pods = self.pods.lock().unwrap();
+        let mut available = 0;
+        let mut unavailable = 0;
+        let mut terminating = 0;
+        let mut unknown = 0;
+        for pod in pods.values() {
+            match WorkspacePhase::from_pod(pod) {
+                WorkspacePhase::Ready => available += 1,
+                WorkspacePhase::Starting => unavailable += 1,
+                WorkspacePhase::Terminating => terminating += 1,
+                WorkspacePhase::Unknown => unknown += 1,
+                WorkspacePhase::NotFound => {}
+            }
+        }
+        op.metrics().workspace_available_count.set(available);
+        op.metrics().workspace_unavailable_count.set(unavailable);
+        op.metrics().workspace_terminating_count.set(terminating);
+        op.metrics().workspace_unknown_count.set(unknown);
+    }
+}
+
+fn workspace_username(labels: Option<&std::collections::BTreeMap<String, String>>) -> Option<String> {
+    labels?.get(Operator::WORKSPACE_USER_LABEL).cloned()
+}
+
+/// Spawn the pod/service/node watchers that keep the operator's status
+/// cache and workspace gauges up to date.
+pub(super) fn spawn(op: Operator) {
+    tokio::task::spawn(watch_pods(op.clone()));
+    tokio::task::spawn(watch_services(op.clone()));
+    tokio::task::spawn(watch_volume_claims(op.clone()));
+    tokio::task::spawn(watch_nodes(op));
+}
+
+async fn watch_pods(op: Operator) {
+    let pod_label = Operator::workspace_pod_label();
+    let mut stream = Box::pin(op.client().watch_pods(op.namespace(), Some(pod_label)));
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(pod)) => {
+                if let Some(name) = pod.metadata.name.clone() {
+                    enqueue_autoshutdown_reconcile(&op, name);
+                }
+                if let Some(username) = workspace_username(pod.metadata.labels.as_ref()) {
+                    op.0.cache.pods.lock().unwrap().insert(username, pod);
+                } else {
+                    tracing::warn!(pod=?pod.metadata.name, "workspace pod has no user label, ignoring");
+                }
+                op.0.cache.update_metrics(&op);
+            }
+            Ok(Event::Deleted(pod)) => {
+                if let Some(username) = workspace_username(pod.metadata.labels.as_ref()) {
+                    op.0.cache.pods.lock().unwrap().remove(&username);
+                }
+                op.0.cache.update_metrics(&op);
+            }
+            Ok(Event::Restarted(pods)) => {
+                let mut cache = op.0.cache.pods.lock().unwrap();
+                cache.clear();
+                for pod in pods {
+                    if let Some(name) = pod.metadata.name.clone() {
+                        enqueue_autoshutdown_reconcile(&op, name);
+                    }
+                    if let Some(username) = workspace_username(pod.metadata.labels.as_ref()) {
+                        cache.insert(username, pod);
+                    }
+                }
+                drop(cache);
+                op.0.cache.pods_synced.store(true, Ordering::SeqCst);
+                op.0.cache.update_metrics(&op);
+            }
+            Err(error) => {
+                tracing::warn!(?error, "pod watcher error, resyncing");
+            }
+        }
+    }
+}
+
+/// Enqueue `pod_name` for an autoshutdown reconcile, if autoshutdown is
+/// configured at all. A no-op otherwise, so pods aren't needlessly requeued
+/// when the feature is disabled.
+fn enqueue_autoshutdown_reconcile(op: &Operator, pod_name: String) {
+    if !op.config().autoshutdown_enabled() {
+        return;
+    }
+    // The receiver only goes away with the operator itself, so a failed
+    // send just means we're shutting down.
+    let _ = op.0.autoshutdown_queue.send(pod_name);
+}
+
+/// Drain the autoshutdown reconcile queue, reconciling one pod at a time
+/// and requeuing it after [`AUTOSHUTDOWN_REQUEUE_INTERVAL`] so its idle
+/// timers keep advancing even without further pod events.
+///
+/// Replaces the old fixed-interval sweep over every cached pod - since
+/// reconciles are now triggered per pod, only pods that actually exist and
+/// have changed (or are due for a timer tick) get checked.
+pub(super) async fn run_autoshutdown_queue(op: Operator, mut queue: mpsc::UnboundedReceiver<String>) {
+    while let Some(pod_name) = queue.recv().await {
+        let still_exists = match reconcile_autoshutdown(&op, &pod_name).await {
+            Ok(still_exists) => still_exists,
+            Err(error) => {
+                tracing::error!(?error, pod = %pod_name, "could not process pod autoshutdown");
+                true
+            }
+        };
+
+        // Stop requeuing once the pod is gone - its Deleted watch event
+        // already cleaned up everything else there is to do.
+        if still_exists {
+            let op = op.clone();
+            tokio::task::spawn(async move {
+                tokio::time::sleep(AUTOSHUTDOWN_REQUEUE_INTERVAL).await;
+                enqueue_autoshutdown_reconcile(&op, pod_name);
+            });
+        }
+    }
+}
+
+/// Reconcile a single pod's autoshutdown state. Returns whether the pod
+/// still exists, so [`run_autoshutdown_queue`] knows whether to requeue it.
+async fn reconcile_autoshutdown(op: &Operator, pod_name: &str) -> Result<bool, crate::AnyError> {
+    let pod = match op.client().pod_opt(op.namespace(), pod_name).await? {
+        Some(pod) => pod,
+        None => return Ok(false),
+    };
+    let metrics = op.pod_metrics_opt(pod_name).await;
+    op.process_pod_autoshutdown(pod, metrics).await?;
+    Ok(true)
+}
+
+async fn watch_services(op: Operator) {
+    let mut stream = Box::pin(op.client().watch_services(op.namespace()));
+
+    // Services don't carry the workspace-user label (only a selector using
+    // it), so the username is recovered from the deterministic
+    // `workspace-<username>` service name instead.
+    let username_of = |service: &Service| -> Option<Username> {
+        service
+            .metadata
+            .name
+            .as_ref()?
+            .strip_prefix("workspace-")
+            .map(|s| s.to_string())
+    };
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(service)) => {
+                if let Some(username) = username_of(&service) {
+                    op.0.cache.services.lock().unwrap().insert(username, service);
+                }
+            }
+            Ok(Event::Deleted(service)) => {
+                if let Some(username) = username_of(&service) {
+                    op.0.cache.services.lock().unwrap().remove(&username);
+                }
+            }
+            Ok(Event::Restarted(services)) => {
+                let mut cache = op.0.cache.services.lock().unwrap();
+                cache.clear();
+                for service in services {
+                    if let Some(username) = username_of(&service) {
+                        cache.insert(username, service);
+                    }
+                }
+                drop(cache);
+                op.0.cache.services_synced.store(true, Ordering::SeqCst);
+            }
+            Err(error) => {
+                tracing::warn!(?error, "service watcher error, resyncing");
+            }
+        }
+    }
+}
+
+/// Mirrors [`watch_services`], but for workspace home-directory PVCs - see
+/// [`Cache::volume_claim`].
+async fn watch_volume_claims(op: Operator) {
+    let mut stream = Box::pin(op.client().watch_volume_claims(op.namespace()));
+
+    let username_of = |claim: &PersistentVolumeClaim| -> Option<Username> {
+        claim
+            .metadata
+            .name
+            .as_ref()?
+            .strip_prefix("workspace-")
+            .map(|s| s.to_string())
+    };
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(claim)) => {
+                if let Some(username) = username_of(&claim) {
+                    op.0.cache.volume_claims.lock().unwrap().insert(username, claim);
+                }
+            }
+            Ok(Event::Deleted(claim)) => {
+                if let Some(username) = username_of(&claim) {
+                    op.0.cache.volume_claims.lock().unwrap().remove(&username);
+                }
+            }
+            Ok(Event::Restarted(claims)) => {
+                let mut cache = op.0.cache.volume_claims.lock().unwrap();
+                cache.clear();
+                for claim in claims {
+                    if let Some(username) = username_of(&claim) {
+                        cache.insert(username, claim);
+                    }
+                }
+                drop(cache);
+                op.0.cache.volume_claims_synced.store(true, Ordering::SeqCst);
+            }
+            Err(error) => {
+                tracing::warn!(?error, "volume claim watcher error, resyncing");
+            }
+        }
+    }
+}
+
+async fn watch_nodes(op: Operator) {
+    let mut stream = Box::pin(op.client().watch_nodes());
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(node)) => {
+                if let Some(name) = node.metadata.name.clone() {
+                    op.0.cache.nodes.lock().unwrap().insert(name, node);
+                }
+            }
+            Ok(Event::Deleted(node)) => {
+                if let Some(name) = node.metadata.name.as_ref() {
+                    op.0.cache.nodes.lock().unwrap().remove(name);
+                }
+            }
+            Ok(Event::Restarted(nodes)) => {
+                let mut cache = op.0.cache.nodes.lock().unwrap();
+                cache.clear();
+                for node in nodes {
+                    if let Some(name) = node.metadata.name.clone() {
+                        cache.insert(name, node);
+                    }
+                }
+            }
+            Err(error) => {
+                tracing::warn!(?error, "node watcher error, resyncing");
+            }
+        }
+    }
+}
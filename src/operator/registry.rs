@@ -0,0 +1,164 @@
+//! Minimal Docker Registry HTTP API v2 (OCI distribution spec) client used
+//! to detect when a workspace's configured image tag has moved to a new
+//! digest upstream.
+//!
+//! Only the read-only "resolve a tag to its current manifest digest" path
+//! is implemented - enough to drive
+//! [`super::Operator::check_image_updates`]. Authentication follows the
+//! anonymous-token flow used by Docker Hub and most registries implementing
+//! the spec: an unauthenticated manifest request answers with a
+//! `WWW-Authenticate` header pointing at a token endpoint, which is then
+//! used to retry the request with a bearer token.
+
+use anyhow::Context;
+
+use crate::AnyError;
+
+const MANIFEST_ACCEPT: &str = "application/vnd.docker.distribution.manifest.v2+json, application/vnd.docker.distribution.manifest.list.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.oci.image.index.v1+json";
+
+/// A parsed `[registry/]repository[:tag]` image reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImageReference {
+    registry: String,
+    repository: String,
+    tag: String,
+}
+
+impl ImageReference {
+    /// Parse an image string such as `ubuntu`, `library/ubuntu:22.04`, or
+    /// `ghcr.io/foo/bar:latest`, defaulting to Docker Hub and the `latest`
+    /// tag the same way `docker pull` resolves a bare reference.
+    fn parse(image: &str) -> Self {
+        let (remainder, tag) = match image.rsplit_once(':') {
+            // A ':' after the last '/' is a tag; one before it is just part
+            // of a registry host:port (eg. "localhost:5000/foo").
+            Some((remainder, tag)) if !tag.contains('/') => (remainder, tag.to_string()),
+            _ => (image, "latest".to_string()),
+        };
+
+        let (registry, repository) = match remainder.split_once('/') {
+            Some((host, rest)) if host.contains('.') || host.contains(':') || host == "localhost" => {
+                (host.to_string(), rest.to_string())
+            }
+            Some(_) => ("registry-1.docker.io".to_string(), remainder.to_string()),
+            None => (
+                "registry-1.docker.io".to_string(),
+                format!("library/{}", remainder),
+            ),
+        };
+
+        Self {
+            registry,
+            repository,
+            tag,
+        }
+    }
+
+    fn manifest_url(&self) -> String {
+        format!(
+            "https://{}/v2/{}/manifests/{}",
+            self.registry, self.repository, self.tag
+        )
+    }
+}
+
+/// Resolve the current manifest digest of `image`'s tag from its registry.
+pub(super) async fn fetch_digest(image: &str) -> Result<String, AnyError> {
+    let reference = ImageReference::parse(image);
+    let client = reqwest::Client::new();
+
+    let response = client
+        .head(reference.manifest_url())
+        .header(reqwest::header::ACCEPT, MANIFEST_ACCEPT)
+        .send()
+        .await
+        .with_context(|| format!("Could not reach registry for image '{}'", image))?;
+
+    let response = if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        let token = fetch_anonymous_token(&client, &response, &reference).await?;
+        client
+            .head(reference.manifest_url())
+            .header(reqwest::header::ACCEPT, MANIFEST_ACCEPT)
+            .bearer_auth(token)
+            .send()
+            .await
+            .with_context(|| format!("Could not reach registry for image '{}'", image))?
+    } else {
+        response
+    };
+
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Registry rejected manifest request for image '{}'", image))?;
+
+    response
+        .headers()
+        .get("Docker-Content-Digest")
+        .and_then(|value| value.to_str().ok())
+        .map(|digest| digest.to_string())
+        .with_context(|| {
+            format!(
+                "Registry response for image '{}' had no Docker-Content-Digest header",
+                image
+            )
+        })
+}
+
+/// Follow the `WWW-Authenticate: Bearer realm="...",service="...",scope="..."`
+/// header of an unauthorized manifest response to obtain an anonymous bearer
+/// token for that one repository/pull scope.
+async fn fetch_anonymous_token(
+    client: &reqwest::Client,
+    unauthorized: &reqwest::Response,
+    reference: &ImageReference,
+) -> Result<String, AnyError> {
+    let header = unauthorized
+        .headers()
+        .get(reqwest::header::WWW_AUTHENTICATE)
+        .and_then(|value| value.to_str().ok())
+        .context("Registry did not advertise a WWW-Authenticate challenge")?;
+
+    let params = parse_bearer_challenge(header)
+        .context("Could not parse registry WWW-Authenticate challenge")?;
+    let realm = params
+        .get("realm")
+        .context("Registry auth challenge has no realm")?;
+    let service = params.get("service").cloned().unwrap_or_default();
+    let scope = params
+        .get("scope")
+        .cloned()
+        .unwrap_or_else(|| format!("repository:{}:pull", reference.repository));
+
+    #[derive(serde::Deserialize)]
+    struct TokenResponse {
+        // Most registries return `token`; some return `access_token` instead.
+        #[serde(alias = "access_token")]
+        token: String,
+    }
+
+    let response: TokenResponse = client
+        .get(realm)
+        .query(&[("service", service.as_str()), ("scope", scope.as_str())])
+        .send()
+        .await
+        .context("Could not reach registry token endpoint")?
+        .error_for_status()
+        .context("Registry token endpoint rejected anonymous auth request")?
+        .json()
+        .await
+        .context("Registry token endpoint returned an unexpected response")?;
+
+    Ok(response.token)
+}
+
+/// Parse a `Bearer key="value",key2="value2"` header into its key/value
+/// parameters.
+fn parse_bearer_challenge(header: &str) -> Option<std::collections::HashMap<String, String>> {
+    let rest = header.strip_prefix("Bearer ")?;
+    let mut params = std::collections::HashMap::new();
+    for part in rest.split(',') {
+        let (key, value) = part.trim().split_once('=')?;
+        params.insert(key.to_string(), value.trim_matches('"').to_string());
+    }
+    Some(params)
+}
@@ -1,6 +1,6 @@
 use k8s_openapi::api::core::v1::{Node, Pod, Service};
 
-use crate::client;
+use crate::client::{self, PodMetrics};
 
 /// The current status phase of a user workspace.
 #[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
@@ -46,12 +46,15 @@ impl WorkspacePhase {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WorkspaceStatus {
     pub phase: WorkspacePhase,
     pub service: Option<Service>,
     pub pod: Option<Pod>,
     pub node: Option<Node>,
+    /// Live resource usage of the pod, from the `metrics.k8s.io` API.
+    /// `None` if the pod is not running or metrics-server is not installed.
+    pub metrics: Option<PodMetrics>,
 }
 
 impl WorkspaceStatus {
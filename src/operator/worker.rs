@@ -0,0 +1,132 @@
+//! Background worker subsystem.
+//!
+//! Recurring reconciliation jobs used to live only as plain loops (see
+//! [`super::Operator::run_loop`]) with no way to tell, from the outside,
+//! whether a given job was stuck, erroring, or simply had nothing to do.
+//! This models each such job as a named [`Worker`], supervised by a
+//! [`WorkerManager`] that publishes the last-observed state of every worker
+//! through a shared [`WorkerRegistry`] - an admin "list workers" endpoint can
+//! read straight from it to debug stuck shutdown behavior.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+
+use crate::AnyError;
+
+/// Outcome of a single [`Worker::work`] step.
+pub enum WorkerState {
+    /// Did useful work; call `work` again immediately rather than sleeping.
+    Busy,
+    /// Nothing to do right now; sleep for the manager's tranquility
+    /// interval before calling `work` again.
+    Idle,
+    /// Permanently finished. The worker is retired and no longer polled.
+    Done,
+}
+
+/// A single named background job driven by a [`WorkerManager`].
+#[async_trait]
+pub(super) trait Worker: Send {
+    /// Stable name this worker's state is published under in the
+    /// [`WorkerRegistry`].
+    fn name(&self) -> &str;
+
+    /// Run one step of work, returning the resulting state.
+    async fn work(&mut self) -> Result<WorkerState, AnyError>;
+}
+
+/// The last-observed state of a single worker, as published by
+/// [`WorkerManager`].
+#[derive(Clone, Debug)]
+pub struct WorkerInfo {
+    pub status: WorkerStatus,
+    pub last_run: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorkerStatus {
+    Busy,
+    Idle,
+    Done,
+    /// Holds the error's `Display` rendering - the registry only needs to
+    /// surface it, not act on it.
+    Error(String),
+}
+
+/// Shared, queryable view of every worker's last-observed state.
+pub type WorkerRegistry = Arc<Mutex<HashMap<String, WorkerInfo>>>;
+
+/// Spawns and supervises a set of [`Worker`]s, publishing their state
+/// through a shared [`WorkerRegistry`].
+pub(super) struct WorkerManager {
+    registry: WorkerRegistry,
+    tranquility_interval: Duration,
+}
+
+impl WorkerManager {
+    pub(super) fn new(tranquility_interval: Duration) -> Self {
+        Self {
+            registry: Arc::new(Mutex::new(HashMap::new())),
+            tranquility_interval,
+        }
+    }
+
+    /// A cloneable handle to the registry workers publish their state to.
+    pub(super) fn registry(&self) -> WorkerRegistry {
+        self.registry.clone()
+    }
+
+    /// Spawn `worker` onto the Tokio executor. It runs until it returns
+    /// [`WorkerState::Done`] or the process exits; errors are recorded in
+    /// the registry and retried with exponential backoff rather than
+    /// killing the task.
+    pub(super) fn spawn(&self, mut worker: Box<dyn Worker>) {
+        let registry = self.registry.clone();
+        let tranquility_interval = self.tranquility_interval;
+
+        tokio::task::spawn(async move {
+            let name = worker.name().to_string();
+            let mut error_backoff = tranquility_interval;
+
+            loop {
+                let result = worker.work().await;
+
+                let status = match &result {
+                    Ok(WorkerState::Busy) => WorkerStatus::Busy,
+                    Ok(WorkerState::Idle) => WorkerStatus::Idle,
+                    Ok(WorkerState::Done) => WorkerStatus::Done,
+                    Err(err) => WorkerStatus::Error(format!("{:#}", err)),
+                };
+                registry.lock().unwrap().insert(
+                    name.clone(),
+                    WorkerInfo {
+                        status,
+                        last_run: SystemTime::now(),
+                    },
+                );
+
+                match result {
+                    Ok(WorkerState::Busy) => continue,
+                    Ok(WorkerState::Idle) => {
+                        error_backoff = tranquility_interval;
+                        tokio::time::sleep(tranquility_interval).await;
+                    }
+                    Ok(WorkerState::Done) => {
+                        tracing::info!(worker=%name, "worker finished, retiring");
+                        break;
+                    }
+                    Err(err) => {
+                        tracing::error!(worker=%name, error=?err, backoff=?error_backoff, "worker step failed, retrying with backoff");
+                        tokio::time::sleep(error_backoff).await;
+                        error_backoff = (error_backoff * 2).min(Duration::from_secs(60 * 5));
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,169 @@
+//! Declarative `Workspace` CRD reconciler, driven by `kube_runtime::Controller`.
+//!
+//! Unlike the ad hoc `ensure_user_pod`/`ensure_user_service`/
+//! `ensure_user_home_volume` calls triggered directly by API requests, this
+//! continuously repairs drift: desired state lives in a `Workspace` object
+//! in etcd, and every watch event - including someone deleting a child
+//! Service by hand - triggers a reconcile that restores it. The PVC,
+//! Service and Pod are owned by the `Workspace` via owner references, so
+//! deleting it is enough to have Kubernetes garbage-collect all of them.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    ResourceExt,
+};
+use kube_runtime::controller::{Context, Controller, ReconcilerAction};
+
+use crate::{
+    config,
+    workspace::{Workspace, WorkspaceCrdStatus},
+    AnyError,
+};
+
+use super::{types::WorkspacePhase, Operator};
+
+/// Wraps [`AnyError`]/[`kube::Error`] so they can be used as the error type
+/// of a [`kube_runtime::Controller`], which requires `std::error::Error`
+/// (unlike `anyhow::Error`, which deliberately doesn't implement it).
+#[derive(Debug)]
+struct ReconcileError(AnyError);
+
+impl std::fmt::Display for ReconcileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for ReconcileError {}
+
+impl From<AnyError> for ReconcileError {
+    fn from(err: AnyError) -> Self {
+        Self(err)
+    }
+}
+
+impl From<kube::Error> for ReconcileError {
+    fn from(err: kube::Error) -> Self {
+        Self(err.into())
+    }
+}
+
+/// Spawn the `Workspace` CRD controller loop. Runs until the process
+/// exits; a failed reconcile is logged and the object is requeued by
+/// [`error_policy`] rather than bringing down the loop.
+pub(super) fn spawn(op: Operator) {
+    tokio::task::spawn(run(op));
+}
+
+async fn run(op: Operator) {
+    let api = op.client().api::<Workspace>(Some(op.namespace()));
+
+    Controller::new(api, ListParams::default())
+        // Watch the owned children too, so eg. a hand-deleted Service
+        // triggers a reconcile of its owning `Workspace` right away.
+        .owns(
+            op.client().api::<k8s_openapi::api::core::v1::Service>(Some(op.namespace())),
+            ListParams::default(),
+        )
+        .owns(
+            op.client().api::<k8s_openapi::api::core::v1::Pod>(Some(op.namespace())),
+            ListParams::default(),
+        )
+        .run(reconcile, error_policy, Context::new(op))
+        .for_each(|res| async move {
+            if let Err(error) = res {
+                tracing::warn!(?error, "workspace reconcile failed");
+            }
+        })
+        .await;
+}
+
+fn error_policy(_error: &ReconcileError, _ctx: Context<Operator>) -> ReconcilerAction {
+    ReconcilerAction {
+        requeue_after: Some(Duration::from_secs(30)),
+    }
+}
+
+fn workspace_phase_str(phase: &WorkspacePhase) -> &'static str {
+    match phase {
+        WorkspacePhase::NotFound => "not_found",
+        WorkspacePhase::Starting => "starting",
+        WorkspacePhase::Ready => "ready",
+        WorkspacePhase::Terminating => "terminating",
+        WorkspacePhase::Unknown => "unknown",
+    }
+}
+
+async fn reconcile(
+    workspace: Arc<Workspace>,
+    ctx: Context<Operator>,
+) -> Result<ReconcilerAction, ReconcileError> {
+    let op = ctx.get_ref();
+
+    let owner = workspace
+        .controller_owner_ref(&())
+        .ok_or_else(|| AnyError::msg("Workspace has no name/uid yet, cannot own children"))?;
+
+    let user = config::User {
+        username: workspace.spec.username.clone(),
+        ssh_public_key: workspace.spec.ssh_public_key.clone(),
+        home_volume_size: workspace.spec.storage_size.clone(),
+    };
+
+    let mut pod_template = op.config().pod_template.clone();
+    if let Some(image) = &workspace.spec.image {
+        let container = pod_template.containers.first_mut();
+        match container {
+            Some(container) => container.image = Some(image.clone()),
+            None => pod_template.containers.push(k8s_openapi::api::core::v1::Container {
+                image: Some(image.clone()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    // The `StatefulSet` backend provisions its own home volume via
+    // `volumeClaimTemplates`, so only the bare-`Pod` backend needs a
+    // separately managed/owned `PersistentVolumeClaim` here.
+    if op.config().workspace_backend == config::WorkspaceBackend::Pod {
+        let home_volume = op.ensure_user_home_volume(&user).await?;
+        op.set_owner_reference(home_volume, &owner).await?;
+    }
+
+    let secret = op.ensure_user_ssh_secret(&user).await?;
+    op.set_owner_reference(secret, &owner).await?;
+
+    let status = op.ensure_user_workspace(&user, &pod_template).await?;
+    if let Some(service) = status.service.clone() {
+        op.set_owner_reference(service, &owner).await?;
+    }
+    if let Some(pod) = status.pod.clone() {
+        op.set_owner_reference(pod, &owner).await?;
+    }
+
+    let crd_status = WorkspaceCrdStatus {
+        phase: workspace_phase_str(&status.phase).to_string(),
+        node_ip: status.public_address(),
+        ssh_port: status.ssh_port(),
+    };
+
+    let api = op.client().api::<Workspace>(Some(op.namespace()));
+    let name = workspace
+        .metadata
+        .name
+        .as_deref()
+        .ok_or_else(|| AnyError::msg("Workspace has no name"))?;
+    api.patch_status(
+        name,
+        &PatchParams::default(),
+        &Patch::Merge(serde_json::json!({ "status": crd_status })),
+    )
+    .await?;
+
+    Ok(ReconcilerAction {
+        requeue_after: Some(Duration::from_secs(60)),
+    })
+}
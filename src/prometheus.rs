@@ -38,11 +38,11 @@ pub struct Endpoint {
 #[derive(Clone, Hash, PartialEq, Eq, Encode)]
 struct Labels {}
 
-type Registry = prometheus_client::registry::Registry<
+pub(crate) type Registry = prometheus_client::registry::Registry<
     Box<dyn prometheus_client::encoding::text::SendEncodeMetric>,
 >;
 
-fn build_registry(metrics: &OperatorMetrics) -> Registry {
+pub(crate) fn build_registry(metrics: &OperatorMetrics) -> Registry {
     let mut reg = Registry::default();
 
     reg.register(
@@ -60,6 +60,61 @@ fn build_registry(metrics: &OperatorMetrics) -> Registry {
         "Number of unavailable (failing) workspaces.",
         Box::new(metrics.workspace_unavailable_count.clone()),
     );
+    reg.register(
+        "kube_workspace_terminating_count",
+        "Number of workspace pods currently terminating.",
+        Box::new(metrics.workspace_terminating_count.clone()),
+    );
+    reg.register(
+        "kube_workspace_unknown_count",
+        "Number of workspace pods in an unknown phase.",
+        Box::new(metrics.workspace_unknown_count.clone()),
+    );
+    reg.register(
+        "kube_workspace_pod_start_count",
+        "Total number of workspace pods created.",
+        Box::new(metrics.pod_start_count.clone()),
+    );
+    reg.register(
+        "kube_workspace_pod_shutdown_count",
+        "Total number of workspace pods/statefulsets torn down.",
+        Box::new(metrics.pod_shutdown_count.clone()),
+    );
+    reg.register(
+        "kube_workspace_auto_shutdown_count",
+        "Total number of workspace pods shut down automatically due to being idle.",
+        Box::new(metrics.auto_shutdown_count.clone()),
+    );
+    reg.register(
+        "kube_workspace_api_requests_count",
+        "Total /api/query requests handled, labeled by query variant.",
+        Box::new(metrics.api_requests_total.clone()),
+    );
+    reg.register(
+        "kube_workspace_api_errors_count",
+        "Total /api/query requests that returned an error.",
+        Box::new(metrics.api_errors_total.clone()),
+    );
+    reg.register(
+        "kube_workspace_cpu_idle_seconds",
+        "Seconds since a user's workspace pod was last observed CPU-active.",
+        Box::new(metrics.cpu_idle_seconds.clone()),
+    );
+    reg.register(
+        "kube_workspace_memory_idle_seconds",
+        "Seconds since a user's workspace pod was last observed memory-active.",
+        Box::new(metrics.memory_idle_seconds.clone()),
+    );
+    reg.register(
+        "kube_workspace_network_idle_seconds",
+        "Seconds since a user's workspace pod was last observed network-active.",
+        Box::new(metrics.network_idle_seconds.clone()),
+    );
+    reg.register(
+        "kube_workspace_pod_ready_latency_seconds",
+        "Time from creating a workspace pod to it becoming ready.",
+        Box::new(metrics.pod_ready_latency.clone()),
+    );
 
     reg
 }
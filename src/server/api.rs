@@ -1,9 +1,15 @@
+use anyhow::Context;
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 
+use crate::{
+    client::{self, PodMetrics},
+    AnyError,
+};
+
 pub(super) async fn run_query(
     server: &super::Server,
     query: &Query,
-) -> Result<QueryOutput, anyhow::Error> {
+) -> Result<QueryOutput, ApiError> {
     let op = &server.operator;
 
     tracing::trace!(?query, "Handling API request");
@@ -12,9 +18,15 @@ pub(super) async fn run_query(
             let config = op.config();
 
             let user = op
-                .config()
-                .verify_user(&create.username, &create.ssh_public_key)?;
-            let status = op.ensure_user_pod(user, &config.pod_template).await?;
+                .authenticate(&create.username, &create.ssh_public_key, create.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+
+            let status = if create.wait_until_ready.unwrap_or(false) {
+                op.ensure_user_workspace_ready(&user, &config.pod_template).await?
+            } else {
+                op.ensure_user_workspace(&user, &config.pod_template).await?
+            };
 
             let addr = status.public_address();
             let port = status.ssh_port();
@@ -23,7 +35,10 @@ pub(super) async fn run_query(
                 .zip(port)
                 .map(|(address, port)| SshAddress { address, port });
 
-            let info = status.pod.as_ref().map(WorkspaceInfo::from_pod);
+            let info = status
+                .pod
+                .as_ref()
+                .map(|pod| WorkspaceInfo::from_pod(pod, status.metrics.as_ref()));
 
             Ok(QueryOutput::PodStart(WorkspaceStatus {
                 username: user.username.clone(),
@@ -34,16 +49,20 @@ pub(super) async fn run_query(
         }
         Query::PodStatus(req) => {
             let user = op
-                .config()
-                .verify_user(&req.username, &req.ssh_public_key)?;
-            let status = op.workspace_status(user).await?;
+                .authenticate(&req.username, &req.ssh_public_key, req.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+            let status = op.workspace_status(&user).await?;
 
             let addr = status.public_address();
             let port = status.ssh_port();
             let ssh_address = addr
                 .zip(port)
                 .map(|(address, port)| SshAddress { address, port });
-            let info = status.pod.as_ref().map(WorkspaceInfo::from_pod);
+            let info = status
+                .pod
+                .as_ref()
+                .map(|pod| WorkspaceInfo::from_pod(pod, status.metrics.as_ref()));
 
             Ok(QueryOutput::PodStatus(WorkspaceStatus {
                 username: user.username.clone(),
@@ -54,13 +73,64 @@ pub(super) async fn run_query(
         }
         Query::PodStop(req) => {
             let user = op
-                .config()
-                .verify_user(&req.username, &req.ssh_public_key)?;
-            if op.get_user_pod_opt(user).await?.is_some() {
-                op.user_pod_shutdown(user).await?;
+                .authenticate(&req.username, &req.ssh_public_key, req.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+            if op.get_user_pod_opt(&user).await?.is_some() {
+                op.user_workspace_shutdown(&user).await?;
             }
             Ok(QueryOutput::PodStop {})
         }
+        Query::PodExec(req) => {
+            let user = op
+                .authenticate(&req.username, &req.ssh_public_key, req.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+            let command: Vec<&str> = req.command.iter().map(String::as_str).collect();
+            let output = op
+                .exec_user_pod(
+                    &user,
+                    command,
+                    req.container.as_deref(),
+                    req.tty.unwrap_or(false),
+                )
+                .await?;
+
+            Ok(QueryOutput::PodExec(PodExecResult {
+                stdout: output.stdout,
+                stderr: output.stderr,
+                success: output.success,
+                exit_code: output.exit_code,
+            }))
+        }
+        Query::PodCopyIn(req) => {
+            let user = op
+                .authenticate(&req.username, &req.ssh_public_key, req.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+            let archive = base64::decode(&req.archive_base64)
+                .context("archive_base64 is not valid base64")
+                .map_err(ApiError::BadRequest)?;
+            let output = op.copy_into_user_pod(&user, &req.dest, archive).await?;
+
+            Ok(QueryOutput::PodCopyIn(PodCopyResult {
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                success: output.success,
+            }))
+        }
+        Query::PodCopyOut(req) => {
+            let user = op
+                .authenticate(&req.username, &req.ssh_public_key, req.bearer_token.as_deref())
+                .await
+                .map_err(ApiError::Unauthorized)?;
+            let output = op.copy_from_user_pod(&user, &req.src).await?;
+
+            Ok(QueryOutput::PodCopyOut(PodCopyOutResult {
+                archive_base64: base64::encode(&output.stdout),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                success: output.success,
+            }))
+        }
     }
 }
 
@@ -71,24 +141,126 @@ pub enum Query {
     PodStart(PodStartRequest),
     PodStatus(PodStatusRequest),
     PodStop(PodStopRequest),
+    PodExec(PodExecRequest),
+    PodCopyIn(PodCopyInRequest),
+    PodCopyOut(PodCopyOutRequest),
+}
+
+impl Query {
+    /// Short, stable label for this query variant, used as the `query`
+    /// label of the `kube_workspace_api_requests_count` metric.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::PodStart(_) => "pod_start",
+            Self::PodStatus(_) => "pod_status",
+            Self::PodStop(_) => "pod_stop",
+            Self::PodExec(_) => "pod_exec",
+            Self::PodCopyIn(_) => "pod_copy_in",
+            Self::PodCopyOut(_) => "pod_copy_out",
+        }
+    }
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct PodStartRequest {
     pub username: String,
     pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
+    /// If set, block until the pod is actually reachable (all containers
+    /// ready) instead of returning as soon as the pod object exists, see
+    /// [`crate::operator::Operator::ensure_user_workspace_ready`]. Bounded by
+    /// [`crate::config::Config::pod_ready_timeout`]; on timeout the request
+    /// fails with [`ApiError::StillStarting`] rather than succeeding with a
+    /// not-yet-reachable pod.
+    pub wait_until_ready: Option<bool>,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct PodStatusRequest {
     pub username: String,
     pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
 }
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct PodStopRequest {
     pub username: String,
     pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodExecRequest {
+    pub username: String,
+    pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
+    /// Command and arguments to execute inside the workspace pod.
+    pub command: Vec<String>,
+    /// Container to run the command in. Defaults to the main workspace
+    /// container.
+    pub container: Option<String>,
+    /// Allocate a TTY for the command. When enabled, stderr is merged into
+    /// stdout, matching the behavior of the Kubernetes exec API.
+    pub tty: Option<bool>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PodExecResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub success: bool,
+    /// The command's numeric exit code, if it could be recovered.
+    pub exit_code: Option<i32>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodCopyInRequest {
+    pub username: String,
+    pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
+    /// Destination path inside the workspace pod, extracted relative to the
+    /// user's home directory unless absolute. Must stay within the home
+    /// directory.
+    pub dest: String,
+    /// Base64-encoded tar archive to extract at `dest`.
+    pub archive_base64: String,
 }
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PodCopyResult {
+    pub stderr: String,
+    pub success: bool,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PodCopyOutRequest {
+    pub username: String,
+    pub ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    pub bearer_token: Option<String>,
+    /// Source path inside the workspace pod, relative to the user's home
+    /// directory unless absolute. Must stay within the home directory.
+    pub src: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PodCopyOutResult {
+    /// Base64-encoded tar archive of `src`.
+    pub archive_base64: String,
+    pub stderr: String,
+    pub success: bool,
+}
+
 #[derive(serde::Serialize, Clone, Debug)]
 pub struct SshAddress {
     pub address: String,
@@ -101,10 +273,18 @@ pub struct WorkspaceInfo {
     pub image: String,
     pub memory_limit: Option<Quantity>,
     pub cpu_limit: Option<Quantity>,
+    /// Live memory usage, in bytes, from the `metrics.k8s.io` API.
+    /// `None` if metrics-server is not installed or has not scraped the pod
+    /// yet.
+    pub memory_usage: Option<i64>,
+    /// Live CPU usage from the `metrics.k8s.io` API.
+    /// `None` if metrics-server is not installed or has not scraped the pod
+    /// yet.
+    pub cpu_usage: Option<i64>,
 }
 
 impl WorkspaceInfo {
-    pub fn from_pod(pod: &k8s_openapi::api::core::v1::Pod) -> Self {
+    pub fn from_pod(pod: &k8s_openapi::api::core::v1::Pod, metrics: Option<&PodMetrics>) -> Self {
         let container = pod.spec.as_ref().and_then(|s| s.containers.first());
 
         let limits = container
@@ -118,10 +298,15 @@ impl WorkspaceInfo {
         let memory_limit = limits.and_then(|l| l.get("memory").cloned());
         let cpu_limit = limits.and_then(|l| l.get("cpu").cloned());
 
+        let cpu_usage = metrics.and_then(|m| client::pod_metrics_total_cpu(m).ok());
+        let memory_usage = metrics.and_then(|m| client::pod_metrics_total_memory(m).ok());
+
         Self {
             image,
             memory_limit,
             cpu_limit,
+            memory_usage,
+            cpu_usage,
         }
     }
 }
@@ -141,6 +326,9 @@ pub enum QueryOutput {
     PodStart(WorkspaceStatus),
     PodStatus(WorkspaceStatus),
     PodStop {},
+    PodExec(PodExecResult),
+    PodCopyIn(PodCopyResult),
+    PodCopyOut(PodCopyOutResult),
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -148,3 +336,66 @@ pub enum ApiResult<T> {
     Ok(T),
     Error { message: String },
 }
+
+/// Typed failure of [`run_query`], so [`super::api_query`] can answer with a
+/// real HTTP status code instead of always 200, while the response body
+/// keeps using the existing [`ApiResult::Error`] shape.
+#[derive(Debug)]
+pub enum ApiError {
+    /// Username/SSH key pair didn't match the configured whitelist.
+    Unauthorized(AnyError),
+    /// Referenced workspace/resource does not exist.
+    NotFound(AnyError),
+    /// Malformed request, eg. an invalid base64 payload. A copy path
+    /// escaping the home directory is rejected by the operator and reported
+    /// as [`Self::Internal`], not as this variant.
+    BadRequest(AnyError),
+    /// Anything else - Kubernetes API failures, timeouts, etc.
+    Internal(AnyError),
+    /// `wait_until_ready` was requested but the pod didn't become reachable
+    /// within [`crate::config::Config::pod_ready_timeout`]. Distinct from
+    /// [`Self::Internal`] so callers can tell "still starting, try again"
+    /// apart from a genuine failure.
+    StillStarting(AnyError),
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> http::StatusCode {
+        match self {
+            Self::Unauthorized(_) => http::StatusCode::UNAUTHORIZED,
+            Self::NotFound(_) => http::StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => http::StatusCode::BAD_REQUEST,
+            Self::Internal(_) => http::StatusCode::INTERNAL_SERVER_ERROR,
+            Self::StillStarting(_) => http::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unauthorized(err)
+            | Self::NotFound(err)
+            | Self::BadRequest(err)
+            | Self::Internal(err)
+            | Self::StillStarting(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<AnyError> for ApiError {
+    fn from(err: AnyError) -> Self {
+        Self::Internal(err)
+    }
+}
+
+impl From<crate::operator::WaitUntilReadyError> for ApiError {
+    fn from(err: crate::operator::WaitUntilReadyError) -> Self {
+        match err {
+            crate::operator::WaitUntilReadyError::Timeout(_) => Self::StillStarting(err.into()),
+            crate::operator::WaitUntilReadyError::Other(err) => Self::Internal(err),
+        }
+    }
+}
@@ -2,19 +2,51 @@
 
 mod api;
 
-use axum::{extract::Extension, response::IntoResponse};
+use axum::{
+    extract::Extension,
+    middleware::{self, Next},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse,
+    },
+};
+use futures::StreamExt;
+use hmac::Mac;
+use sha2::Sha256;
 
 use crate::operator::Operator;
 
 /// Start the webserver.
 ///
-/// Blocks indefinitely.
+/// Listens for SIGTERM/SIGINT and drives a graceful shutdown: flips
+/// `/ready` to 503 immediately (so Kubernetes stops routing new traffic
+/// here), stops accepting new connections, and gives in-flight
+/// `/api/query` calls up to [`crate::config::Config::shutdown_grace_period`] to
+/// finish before the listener is forced closed.
+///
+/// Blocks until shutdown completes instead of panicking on a transport
+/// error.
 pub async fn run_server(op: Operator) {
     let address = op.config().server_address;
+    let grace_period = op.config().shutdown_grace_period;
+    let tls = op.config().tls.clone();
+
+    // `/api/query`, `/api/logs` and `/api/watch` get their own
+    // signature-verification middleware, applied
+    // before the shared `Server` extension is available further down, so
+    // it's nested separately and merged in rather than added as a plain
+    // `.route(...)`.
+    let signed_api_routes = axum::Router::new()
+        .route("/api/query", axum::routing::post(api_query))
+        .route("/api/logs", axum::routing::get(pod_logs))
+        .route("/api/watch", axum::routing::get(watch_workspace))
+        .layer(middleware::from_fn(verify_signature));
 
     let router = axum::Router::new()
         .route("/health", axum::routing::get(health))
-        .route("/api/query", axum::routing::post(api_query))
+        .route("/metrics", axum::routing::get(metrics))
+        .route("/workers", axum::routing::get(list_workers))
+        .merge(signed_api_routes)
         .layer(
             tower::ServiceBuilder::new()
                 .layer(axum::error_handling::HandleErrorLayer::new(
@@ -42,16 +74,118 @@ pub async fn run_server(op: Operator) {
                 .load_shed()
                 .timeout(std::time::Duration::from_secs(5))
                 .layer(tower_http::trace::TraceLayer::new_for_http())
-                .layer(axum::AddExtensionLayer::new(Server { operator: op }))
+                .layer(axum::AddExtensionLayer::new(Server { operator: op.clone() }))
                 .into_inner(),
         );
 
-    tracing::info!(address=%address, "Starting http server");
+    // Shared by both transports below - resolves once SIGTERM/SIGINT
+    // arrives, flipping readiness before the listener stops accepting new
+    // connections.
+    let shutdown_op = op.clone();
+    let shutdown_signal = async move {
+        wait_for_shutdown_signal().await;
+        tracing::info!(grace_period = ?grace_period, "shutdown signal received, draining in-flight requests");
+        shutdown_op.set_ready(false);
+    };
 
-    axum::Server::bind(&address)
-        .serve(router.into_make_service())
-        .await
-        .unwrap();
+    let result = match tls {
+        Some(tls) => {
+            tracing::info!(address=%address, "Starting https server");
+            let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .expect("Could not load TLS cert/key pair");
+
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            tokio::task::spawn(async move {
+                shutdown_signal.await;
+                shutdown_handle.graceful_shutdown(Some(grace_period));
+            });
+
+            axum_server::bind_rustls(address, tls_config)
+                .handle(handle)
+                .serve(router.into_make_service())
+                .await
+                .map_err(crate::AnyError::from)
+        }
+        None => {
+            tracing::info!(address=%address, "Starting http server");
+            // hyper's graceful shutdown waits for every connection to close,
+            // so the grace period is enforced separately once the signal fires.
+            let (signalled_tx, signalled_rx) = tokio::sync::oneshot::channel::<()>();
+            let server = axum::Server::bind(&address)
+                .serve(router.into_make_service())
+                .with_graceful_shutdown(async move {
+                    shutdown_signal.await;
+                    let _ = signalled_tx.send(());
+                });
+            let deadline = async move {
+                match signalled_rx.await {
+                    Ok(()) => tokio::time::sleep(grace_period).await,
+                    Err(_) => futures::future::pending::<()>().await,
+                }
+            };
+            tokio::select! {
+                result = server => result.map_err(crate::AnyError::from),
+                _ = deadline => {
+                    tracing::warn!(grace_period = ?grace_period, "grace period elapsed, closing remaining connections");
+                    Ok(())
+                }
+            }
+        }
+    };
+
+    if let Err(err) = result {
+        tracing::error!(?err, "webserver error");
+    }
+    tracing::info!("webserver shut down");
+}
+
+/// Run the admin HTTP listener exposing Kubernetes liveness/readiness
+/// probes, kept separate from [`run_server`] so `/ready` stays reachable
+/// even while the main listener is draining for a graceful shutdown.
+///
+/// Blocks indefinitely.
+pub async fn run_admin_server(op: Operator) {
+    let address = op.config().admin_address;
+
+    let router = axum::Router::new()
+        .route("/live", axum::routing::get(live))
+        .route("/ready", axum::routing::get(ready))
+        .layer(axum::AddExtensionLayer::new(Server { operator: op }));
+
+    tracing::info!(address=%address, "Starting admin server");
+    if let Err(err) = axum::Server::bind(&address).serve(router.into_make_service()).await {
+        tracing::error!(?err, "admin server error");
+    }
+}
+
+/// Resolve once SIGTERM or (for local/interactive use) SIGINT is received.
+async fn wait_for_shutdown_signal() {
+    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Could not install SIGTERM handler");
+    tokio::select! {
+        _ = sigterm.recv() => tracing::info!("received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => tracing::info!("received SIGINT"),
+    }
+}
+
+/// Always 200 once the process is up - only checks that the admin listener
+/// itself is responsive. See [`ready`] for actual initialization/draining
+/// state.
+async fn live() -> impl IntoResponse {
+    (http::StatusCode::OK, "ok".to_string())
+}
+
+/// 200 once [`crate::operator::Operator::launch`] has finished
+/// initializing (Kubernetes client connected, namespace ensured), and 503
+/// again while [`run_server`] is draining for a graceful shutdown.
+async fn ready(Extension(server): State) -> impl IntoResponse {
+    if server.operator.is_ready() {
+        (http::StatusCode::OK, "ok".to_string())
+    } else {
+        (http::StatusCode::SERVICE_UNAVAILABLE, "not ready".to_string())
+    }
 }
 
 #[derive(Clone)]
@@ -65,17 +199,242 @@ async fn health(Extension(_server): State) -> impl IntoResponse {
     (http::StatusCode::OK, "ok".to_string())
 }
 
+/// Render the same workspace-lifecycle counters as the standalone exporter
+/// (see [`crate::prometheus::run_exporter_service`]), but on the main API
+/// server so deployments that don't want a second listener can still scrape
+/// them alongside `/health`.
+async fn metrics(Extension(server): State) -> impl IntoResponse {
+    let registry = crate::prometheus::build_registry(&server.operator.metrics().clone());
+    let mut buffer = Vec::new();
+    prometheus_client::encoding::text::encode(&mut buffer, &registry).unwrap();
+
+    (
+        [(
+            http::header::CONTENT_TYPE,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )],
+        buffer,
+    )
+}
+
+/// Reported state of a single background worker, for the `/workers` admin
+/// endpoint. Mirrors `operator::WorkerInfo`/`WorkerStatus`, but with
+/// JSON-friendly field types.
+#[derive(serde::Serialize)]
+struct WorkerSnapshot {
+    name: String,
+    status: String,
+    last_run: chrono::DateTime<chrono::Utc>,
+}
+
+/// List the operator's background workers and their last-observed state,
+/// eg. to debug a stuck autoshutdown sweep.
+async fn list_workers(Extension(server): State) -> axum::Json<Vec<WorkerSnapshot>> {
+    let registry = server.operator.worker_registry();
+    let registry = registry.lock().unwrap();
+
+    let mut workers: Vec<WorkerSnapshot> = registry
+        .iter()
+        .map(|(name, info)| WorkerSnapshot {
+            name: name.clone(),
+            status: format!("{:?}", info.status),
+            last_run: info.last_run.into(),
+        })
+        .collect();
+    workers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    axum::Json(workers)
+}
+
+/// Authenticate the calling frontend itself, independent of the per-request
+/// `ssh_public_key` that [`api::run_query`] re-checks against a user.
+///
+/// Only active when [`crate::config::Config::request_signing_psk`] is
+/// configured; without it every request passes through unchecked. When it
+/// is, rejects with 401 unless the request carries an `X-Signature` header
+/// containing the hex-encoded HMAC-SHA256 of the raw request body, computed
+/// with that pre-shared key. Buffers the body to compute the digest, then
+/// reconstructs the request so `Json<api::Query>` can still deserialize it
+/// downstream.
+async fn verify_signature(
+    Extension(server): Extension<Server>,
+    request: axum::http::Request<axum::body::Body>,
+    next: Next<axum::body::Body>,
+) -> Result<axum::response::Response, (http::StatusCode, String)> {
+    let psk = match server.operator.config().request_signing_psk.as_ref() {
+        Some(psk) => psk,
+        None => return Ok(next.run(request).await),
+    };
+
+    let provided_signature = request
+        .headers()
+        .get("X-Signature")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .ok_or((
+            http::StatusCode::UNAUTHORIZED,
+            "Missing X-Signature header".to_string(),
+        ))?;
+
+    let (parts, body) = request.into_parts();
+    let body_bytes = hyper::body::to_bytes(body)
+        .await
+        .map_err(|err| (http::StatusCode::BAD_REQUEST, err.to_string()))?;
+
+    let mut mac = hmac::Hmac::<Sha256>::new_from_slice(psk.as_bytes())
+        .expect("HMAC-SHA256 accepts keys of any length");
+    mac.update(&body_bytes);
+    let expected = mac.finalize().into_bytes();
+
+    let provided = hex::decode(provided_signature.trim())
+        .map_err(|_| (http::StatusCode::UNAUTHORIZED, "Invalid X-Signature encoding".to_string()))?;
+
+    // Constant-time comparison so response timing can't leak how many
+    // leading bytes of the signature matched.
+    let signatures_match =
+        expected.len() == provided.len() && expected.iter().zip(&provided).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0;
+    if !signatures_match {
+        return Err((http::StatusCode::UNAUTHORIZED, "Signature mismatch".to_string()));
+    }
+
+    let request = axum::http::Request::from_parts(parts, axum::body::Body::from(body_bytes));
+    Ok(next.run(request).await)
+}
+
 async fn api_query(
     Extension(server): State,
     query: axum::Json<api::Query>,
-) -> axum::Json<api::ApiResult<api::QueryOutput>> {
+) -> (http::StatusCode, axum::Json<api::ApiResult<api::QueryOutput>>) {
+    server
+        .operator
+        .metrics()
+        .api_requests_total
+        .get_or_create(&crate::operator::QueryLabel {
+            query: query.0.label().to_string(),
+        })
+        .inc();
+
     let res = api::run_query(&server, &query.0).await;
     tracing::trace!(query=?query, response=?res, "api_query_resolved");
-    let output = match res {
-        Ok(out) => api::ApiResult::Ok(out),
-        Err(err) => api::ApiResult::Error {
-            message: err.to_string(),
-        },
-    };
-    axum::Json(output)
+    match res {
+        Ok(out) => (http::StatusCode::OK, axum::Json(api::ApiResult::Ok(out))),
+        Err(err) => {
+            server.operator.metrics().api_errors_total.inc();
+            let status = err.status_code();
+            let output = api::ApiResult::Error {
+                message: err.to_string(),
+            };
+            (status, axum::Json(output))
+        }
+    }
+}
+
+/// Query parameters for the `/api/logs` endpoint.
+#[derive(serde::Deserialize)]
+struct PodLogsParams {
+    username: String,
+    ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    bearer_token: Option<String>,
+    /// Keep the connection open and stream new log lines as they arrive.
+    follow: Option<bool>,
+    /// Only return the last N lines of existing logs.
+    tail_lines: Option<i64>,
+    /// Only return logs newer than this many seconds.
+    since_seconds: Option<i64>,
+}
+
+/// Stream a user's workspace pod logs.
+///
+/// Unlike `/api/query`, this forwards the log bytes directly as they are
+/// read from the Kubernetes API rather than buffering the whole response, so
+/// `follow=true` requests can tail a workspace's startup output live.
+async fn pod_logs(
+    Extension(server): State,
+    axum::extract::Query(params): axum::extract::Query<PodLogsParams>,
+) -> Result<impl IntoResponse, (http::StatusCode, String)> {
+    let user = server
+        .operator
+        .authenticate(&params.username, &params.ssh_public_key, params.bearer_token.as_deref())
+        .await
+        .map_err(|err| (http::StatusCode::FORBIDDEN, err.to_string()))?;
+
+    let stream = server
+        .operator
+        .user_pod_log_stream(
+            &user,
+            params.follow.unwrap_or(false),
+            params.tail_lines,
+            params.since_seconds,
+        )
+        .await
+        .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    let body = axum::body::StreamBody::new(tokio_util::io::ReaderStream::new(stream));
+    Ok(axum::response::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(body)
+        .unwrap())
+}
+
+/// Query parameters for the `/api/watch` endpoint.
+#[derive(serde::Deserialize)]
+struct WatchParams {
+    username: String,
+    ssh_public_key: String,
+    /// Bearer token presented instead of a pre-registered SSH key, when
+    /// the operator is configured for [`crate::config::AuthConfig::Oidc`].
+    bearer_token: Option<String>,
+}
+
+/// Stream `WorkspacePhase`/SSH-address changes for a user's workspace as
+/// Server-Sent Events.
+///
+/// Lets a client that just called `PodStart` learn the moment its
+/// workspace becomes reachable instead of polling `PodStatus` in a loop.
+/// Closes once the workspace reaches `WorkspacePhase::Ready` or a terminal
+/// phase - see [`crate::operator::Operator::watch_workspace_status`].
+async fn watch_workspace(
+    Extension(server): State,
+    axum::extract::Query(params): axum::extract::Query<WatchParams>,
+) -> Result<Sse<impl futures::Stream<Item = Result<Event, std::convert::Infallible>>>, (http::StatusCode, String)> {
+    let user = server
+        .operator
+        .authenticate(&params.username, &params.ssh_public_key, params.bearer_token.as_deref())
+        .await
+        .map_err(|err| (http::StatusCode::FORBIDDEN, err.to_string()))?;
+
+    let username = user.username.clone();
+    let stream = server
+        .operator
+        .clone()
+        .watch_workspace_status(user)
+        .map(move |result| {
+            let status = match result {
+                Ok(status) => status,
+                Err(error) => return Ok(Event::default().event("error").data(error.to_string())),
+            };
+
+            let ssh_address = status
+                .public_address()
+                .zip(status.ssh_port())
+                .map(|(address, port)| api::SshAddress { address, port });
+            let info = status
+                .pod
+                .as_ref()
+                .map(|pod| api::WorkspaceInfo::from_pod(pod, status.metrics.as_ref()));
+
+            let payload = api::WorkspaceStatus {
+                username: username.clone(),
+                phase: status.phase,
+                ssh_address,
+                info,
+            };
+
+            Ok(Event::default().data(serde_json::to_string(&payload).unwrap()))
+        });
+
+    Ok(Sse::new(stream))
 }
@@ -0,0 +1,244 @@
+//! Pluggable sources of the workspace user whitelist.
+//!
+//! [`config::Config::verify_user`] resolves users from the static config
+//! file, which is frozen for the lifetime of the process. [`UserProvider`]
+//! lets that be swapped for a source that can change at runtime, eg. a
+//! database table an admin can edit without restarting the operator.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::StreamExt;
+use kube_runtime::watcher::Event;
+
+use crate::{
+    client::Client,
+    config::{self, User},
+    workspace_user::WorkspaceUser,
+    AnyError,
+};
+
+/// Resolves a username/SSH-public-key pair to a [`User`], or fails if the
+/// pair isn't recognized. Applies the same rules as
+/// [`config::Config::verify_user`] (exact username match, keys compared
+/// after trimming), but is async and returns an owned [`User`].
+#[async_trait]
+pub trait UserProvider: Send + Sync {
+    async fn verify(&self, username: &str, ssh_public_key: &str) -> Result<User, AnyError>;
+
+    /// The full set of usernames this provider currently recognizes, eg. so
+    /// [`crate::operator::Operator::gc_orphaned_workspaces`] can tell a
+    /// legitimate (but perhaps never-logged-in) user apart from one that
+    /// was actually removed.
+    async fn known_usernames(&self) -> Result<HashSet<String>, AnyError>;
+}
+
+/// The long-standing behavior: the user whitelist is whatever was in
+/// `config.json`/`ConfigSource.users` at startup.
+pub struct StaticUserProvider {
+    users: Vec<User>,
+}
+
+impl StaticUserProvider {
+    pub fn new(users: Vec<User>) -> Self {
+        Self { users }
+    }
+}
+
+#[async_trait]
+impl UserProvider for StaticUserProvider {
+    async fn verify(&self, username: &str, ssh_public_key: &str) -> Result<User, AnyError> {
+        let user = self
+            .users
+            .iter()
+            .find(|u| u.username == username)
+            .with_context_not_found(username)?;
+
+        if user.ssh_public_key.trim() != ssh_public_key.trim() {
+            anyhow::bail!("Invalid/unknown ssh public key");
+        }
+        Ok(user.clone())
+    }
+
+    async fn known_usernames(&self) -> Result<HashSet<String>, AnyError> {
+        Ok(self.users.iter().map(|u| u.username.clone()).collect())
+    }
+}
+
+/// Small helper so both providers report the same "unknown user" message.
+trait NotFoundExt<T> {
+    fn with_context_not_found(self, username: &str) -> Result<T, AnyError>;
+}
+
+impl<T> NotFoundExt<T> for Option<T> {
+    fn with_context_not_found(self, username: &str) -> Result<T, AnyError> {
+        self.ok_or_else(|| anyhow::anyhow!("Username '{}' not found", username))
+    }
+}
+
+/// Looks up users in a `users(username, ssh_public_key, home_volume_size)`
+/// table via `sqlx`,
+/// so admins can manage workspace access (Postgres or SQLite, whichever
+/// [`config::UsersProviderConfig::Sql::url`] points at) without redeploying
+/// the operator.
+pub struct SqlUserProvider {
+    pool: sqlx::AnyPool,
+}
+
+impl SqlUserProvider {
+    pub async fn connect(database_url: &str) -> Result<Self, AnyError> {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(database_url).await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl UserProvider for SqlUserProvider {
+    async fn verify(&self, username: &str, ssh_public_key: &str) -> Result<User, AnyError> {
+        let row: Option<(String, String, Option<String>)> = sqlx::query_as(
+            "SELECT username, ssh_public_key, home_volume_size FROM users WHERE username = ?",
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let (username, stored_key, home_volume_size) =
+            row.with_context_not_found(username)?;
+
+        if stored_key.trim() != ssh_public_key.trim() {
+            anyhow::bail!("Invalid/unknown ssh public key");
+        }
+
+        Ok(User {
+            username,
+            ssh_public_key: stored_key,
+            home_volume_size,
+        })
+    }
+
+    async fn known_usernames(&self) -> Result<HashSet<String>, AnyError> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT username FROM users")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|(username,)| username).collect())
+    }
+}
+
+/// Layers a live, watch-driven view of `WorkspaceUser` custom resources on
+/// top of another provider, so cluster admins can grant workspace access
+/// with `kubectl apply` instead of editing `config.json` and restarting -
+/// see [`crate::workspace_user::WorkspaceUser`]. `inner` is always
+/// consulted first, so a `WorkspaceUser` can never shadow a statically
+/// configured user.
+pub struct CrdUserProvider {
+    inner: Box<dyn UserProvider>,
+    users: Arc<Mutex<HashMap<String, User>>>,
+}
+
+impl CrdUserProvider {
+    /// Wrap `inner`, registering the `WorkspaceUser` CRD (unless
+    /// `auto_register` is false, see
+    /// [`config::ConfigSource::auto_register_user_crd`]) and spawning the
+    /// background watcher that keeps the CRD-backed user set up to date.
+    pub async fn new(
+        client: Client,
+        namespace: String,
+        inner: Box<dyn UserProvider>,
+        auto_register: bool,
+    ) -> Result<Self, AnyError> {
+        if auto_register {
+            client
+                .register_crd::<WorkspaceUser>("kube-workspaces.foundational.cc")
+                .await
+                .context("Could not register WorkspaceUser CRD")?;
+        }
+
+        let users = Arc::new(Mutex::new(HashMap::new()));
+        tokio::task::spawn(watch_workspace_users(client, namespace, users.clone()));
+
+        Ok(Self { inner, users })
+    }
+}
+
+#[async_trait]
+impl UserProvider for CrdUserProvider {
+    async fn verify(&self, username: &str, ssh_public_key: &str) -> Result<User, AnyError> {
+        if let Ok(user) = self.inner.verify(username, ssh_public_key).await {
+            return Ok(user);
+        }
+
+        let user = self
+            .users
+            .lock()
+            .unwrap()
+            .get(username)
+            .cloned()
+            .with_context_not_found(username)?;
+
+        if user.ssh_public_key.trim() != ssh_public_key.trim() {
+            anyhow::bail!("Invalid/unknown ssh public key");
+        }
+        Ok(user)
+    }
+
+    async fn known_usernames(&self) -> Result<HashSet<String>, AnyError> {
+        let mut usernames = self.inner.known_usernames().await?;
+        usernames.extend(self.users.lock().unwrap().keys().cloned());
+        Ok(usernames)
+    }
+}
+
+fn workspace_user_to_user(wu: &WorkspaceUser) -> User {
+    User {
+        username: wu.spec.username.clone(),
+        ssh_public_key: wu.spec.ssh_public_key.clone(),
+        home_volume_size: wu.spec.home_volume_size.clone(),
+    }
+}
+
+/// Keep `users` in sync with `WorkspaceUser` objects in `namespace`. Runs
+/// until the process exits; a watch error is logged and the underlying
+/// [`kube_runtime::watcher`] resyncs on its own.
+async fn watch_workspace_users(client: Client, namespace: String, users: Arc<Mutex<HashMap<String, User>>>) {
+    let mut stream = Box::pin(client.watch::<WorkspaceUser>(Some(&namespace)));
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(wu)) => {
+                users.lock().unwrap().insert(wu.spec.username.clone(), workspace_user_to_user(&wu));
+            }
+            Ok(Event::Deleted(wu)) => {
+                users.lock().unwrap().remove(&wu.spec.username);
+            }
+            Ok(Event::Restarted(list)) => {
+                let mut map = users.lock().unwrap();
+                map.clear();
+                for wu in &list {
+                    map.insert(wu.spec.username.clone(), workspace_user_to_user(wu));
+                }
+            }
+            Err(error) => {
+                tracing::warn!(?error, "WorkspaceUser watcher error, resyncing");
+            }
+        }
+    }
+}
+
+/// Build the configured [`UserProvider`] for the operator, layered with
+/// [`CrdUserProvider`] so `WorkspaceUser` objects are always consulted in
+/// addition to whatever [`config::Config::users_provider`] selects.
+pub async fn build(config: &config::Config, client: Client) -> Result<Box<dyn UserProvider>, AnyError> {
+    let base: Box<dyn UserProvider> = match &config.users_provider {
+        config::UsersProviderConfig::Static => Box::new(StaticUserProvider::new(config.users.clone())),
+        config::UsersProviderConfig::Sql { url } => Box::new(SqlUserProvider::connect(url).await?),
+    };
+
+    Ok(Box::new(
+        CrdUserProvider::new(client, config.namespace.clone(), base, config.auto_register_user_crd).await?,
+    ))
+}
@@ -0,0 +1,43 @@
+//! The `Workspace` CustomResourceDefinition.
+//!
+//! A `Workspace` is the declarative counterpart to the ad hoc
+//! `ensure_user_pod`/`ensure_user_service`/`ensure_user_home_volume` calls
+//! triggered by API requests: the desired state (which user, which image,
+//! how much storage) lives in etcd as a `Workspace` object, and
+//! `operator::workspace_controller` continuously reconciles the PVC,
+//! Service and Pod (or StatefulSet, see [`crate::config::WorkspaceBackend`])
+//! it owns to match it - repairing drift such as a child object being
+//! deleted out-of-band.
+
+/// Declarative desired state of a user workspace.
+#[derive(
+    kube::CustomResource, Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq,
+)]
+#[kube(
+    group = "kube-workspaces.foundational.cc",
+    version = "v1",
+    kind = "Workspace",
+    namespaced,
+    status = "WorkspaceCrdStatus",
+    schema = "disabled"
+)]
+pub struct WorkspaceSpec {
+    pub username: String,
+    pub ssh_public_key: String,
+    /// Container image to run. Falls back to the operator's configured
+    /// pod template image if unset.
+    pub image: Option<String>,
+    /// Home volume storage size override (eg. `"20Gi"`). Clamped to the
+    /// operator's configured maximum, see [`crate::config::Config::home_volume_size_for`].
+    pub storage_size: Option<String>,
+}
+
+/// Observed state of a `Workspace`, written back to `.status` by the
+/// controller on every reconcile.
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct WorkspaceCrdStatus {
+    pub phase: String,
+    /// Public node IP/hostname the workspace's SSH port is reachable on.
+    pub node_ip: Option<String>,
+    pub ssh_port: Option<i32>,
+}
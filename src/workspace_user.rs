@@ -0,0 +1,26 @@
+//! The `WorkspaceUser` CustomResourceDefinition.
+//!
+//! Declarative counterpart to `ConfigSource.users`: granting (or revoking)
+//! workspace access is a `kubectl apply`/`kubectl delete` against this CRD
+//! instead of editing `config.json` and restarting the operator, see
+//! [`crate::user_provider::CrdUserProvider`].
+
+/// A single username/SSH-public-key pair allowed to start a workspace,
+/// sourced from the cluster instead of the static config file.
+#[derive(
+    kube::CustomResource, Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq,
+)]
+#[kube(
+    group = "kube-workspaces.foundational.cc",
+    version = "v1",
+    kind = "WorkspaceUser",
+    namespaced,
+    schema = "disabled"
+)]
+pub struct WorkspaceUserSpec {
+    pub username: String,
+    pub ssh_public_key: String,
+    /// Per-user override for the home volume storage size (eg. `"20Gi"`),
+    /// mirroring [`crate::config::User::home_volume_size`].
+    pub home_volume_size: Option<String>,
+}